@@ -5,7 +5,12 @@ pub enum GameControl {
     Down,
     Left,
     Right,
+    Spin,
     Reset,
+    Trajectory,
+    CameraMode,
+    FreeCamera,
+    PhotoMode,
 }
 
 impl GameControl {
@@ -23,7 +28,14 @@ impl GameControl {
             GameControl::Right => {
                 keyboard_input.pressed(KeyCode::D) || keyboard_input.pressed(KeyCode::Right)
             }
+            GameControl::Spin => {
+                keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift)
+            }
             GameControl::Reset => keyboard_input.just_pressed(KeyCode::R),
+            GameControl::Trajectory => keyboard_input.just_pressed(KeyCode::T),
+            GameControl::CameraMode => keyboard_input.just_pressed(KeyCode::C),
+            GameControl::FreeCamera => keyboard_input.just_pressed(KeyCode::F),
+            GameControl::PhotoMode => keyboard_input.just_pressed(KeyCode::P),
         }
     }
 }
@@ -1,4 +1,5 @@
-use bevy::input::mouse::MouseMotion;
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
 
 use crate::actions::game_control::{get_movement, GameControl};
@@ -6,6 +7,13 @@ use crate::GameState;
 
 mod game_control;
 
+/// Touchpads scroll in pixels, this roughly matches them to a mouse wheel's lines.
+const PIXELS_PER_LINE: f32 = 100.;
+/// How far a stick has to be tilted before it counts.
+const STICK_DEADZONE: f32 = 0.15;
+/// How far a fully tilted right stick turns the camera, in the same units as mouse movement.
+const STICK_CAMERA_SPEED: f32 = 15.;
+
 pub struct ActionsPlugin;
 
 // This plugin listens for keyboard input and converts the input into Actions
@@ -13,9 +21,11 @@ pub struct ActionsPlugin;
 impl Plugin for ActionsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Actions>()
+            .init_resource::<GamepadActions>()
             .add_event::<BurstActions>()
             .add_systems(
-                (set_movement_actions, set_burst_actions).in_set(OnUpdate(GameState::Playing)),
+                (set_movement_actions, set_gamepad_actions, set_burst_actions)
+                    .in_set(OnUpdate(GameState::Playing)),
             );
     }
 }
@@ -24,17 +34,52 @@ impl Plugin for ActionsPlugin {
 pub struct Actions {
     pub player_movement: Option<Vec2>,
     pub camera_movement: Option<Vec2>,
+    /// Scroll wheel movement in lines, positive when scrolling up
+    pub camera_zoom: Option<f32>,
+    pub spin: bool,
+}
+
+/// Read from the first connected gamepad, for the second player in split-screen.
+#[derive(Default, Resource)]
+pub struct GamepadActions(pub Actions);
+
+/// Without one it's the keyboard and mouse.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputSource {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+#[derive(SystemParam)]
+pub struct PlayerActions<'w> {
+    actions: Res<'w, Actions>,
+    gamepad_actions: Res<'w, GamepadActions>,
+}
+
+impl PlayerActions<'_> {
+    pub fn get(&self, input_source: Option<&InputSource>) -> &Actions {
+        match input_source.copied().unwrap_or_default() {
+            InputSource::Keyboard => &self.actions,
+            InputSource::Gamepad => &self.gamepad_actions.0,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BurstActions {
     Reset,
+    ToggleTrajectory,
+    CycleCameraMode,
+    ToggleFreeCamera,
+    TogglePhotoMode,
 }
 
 pub fn set_movement_actions(
     mut actions: ResMut<Actions>,
     keyboard_input: Res<Input<KeyCode>>,
     mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
 ) {
     let player_movement = Vec2::new(
         get_movement(GameControl::Right, &keyboard_input)
@@ -43,6 +88,8 @@ pub fn set_movement_actions(
             - get_movement(GameControl::Down, &keyboard_input),
     );
 
+    actions.spin = GameControl::Spin.pressed(&keyboard_input);
+
     if player_movement != Vec2::ZERO {
         actions.player_movement = Some(player_movement.normalize());
     } else {
@@ -62,6 +109,51 @@ pub fn set_movement_actions(
             camera_movement
         });
     }
+
+    if mouse_wheel.is_empty() {
+        actions.camera_zoom = None;
+    } else {
+        actions.camera_zoom = Some(
+            mouse_wheel
+                .iter()
+                .map(|wheel| match wheel.unit {
+                    MouseScrollUnit::Line => wheel.y,
+                    MouseScrollUnit::Pixel => wheel.y / PIXELS_PER_LINE,
+                })
+                .sum(),
+        );
+    }
+}
+
+/// The left stick steers, the right stick turns the camera and the left trigger spins.
+fn set_gamepad_actions(
+    mut gamepad_actions: ResMut<GamepadActions>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    let actions = &mut gamepad_actions.0;
+
+    let Some(gamepad) = gamepads.iter().next() else {
+        *actions = Actions::default();
+        return;
+    };
+
+    let stick = |x, y| {
+        let stick = Vec2::new(
+            axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.),
+            axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.),
+        );
+
+        (stick.length() > STICK_DEADZONE).then_some(stick.clamp_length_max(1.))
+    };
+
+    actions.player_movement = stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY);
+    // Pushing the stick up looks up, where moving the mouse up gives a negative delta
+    actions.camera_movement = stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY)
+        .map(|stick| Vec2::new(stick.x, -stick.y) * STICK_CAMERA_SPEED);
+    actions.camera_zoom = None;
+    actions.spin = buttons.pressed(GamepadButton::new(gamepad, GamepadButtonType::LeftTrigger2));
 }
 
 fn set_burst_actions(
@@ -71,4 +163,20 @@ fn set_burst_actions(
     if GameControl::pressed(&GameControl::Reset, &keyboard_input) {
         burst_actions.send(BurstActions::Reset);
     }
+
+    if GameControl::pressed(&GameControl::Trajectory, &keyboard_input) {
+        burst_actions.send(BurstActions::ToggleTrajectory);
+    }
+
+    if GameControl::pressed(&GameControl::CameraMode, &keyboard_input) {
+        burst_actions.send(BurstActions::CycleCameraMode);
+    }
+
+    if GameControl::pressed(&GameControl::FreeCamera, &keyboard_input) {
+        burst_actions.send(BurstActions::ToggleFreeCamera);
+    }
+
+    if GameControl::pressed(&GameControl::PhotoMode, &keyboard_input) {
+        burst_actions.send(BurstActions::TogglePhotoMode);
+    }
 }
@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_kira_audio::prelude::*;
+
+use crate::{loading::AudioAssets, settings::Settings, util::name_suffix, GameState};
+
+/// How loud the music gets while it's ducked, relative to its normal volume.
+const DUCK_VOLUME: f64 = 0.25;
+const DUCK_TIME: f32 = 2.;
+const VOLUME_FADE: Duration = Duration::from_millis(300);
+const INTENSITY_FADE: Duration = Duration::from_millis(1500);
+/// Sounds further away from the camera than this can't be heard.
+const HEARING_DISTANCE: f32 = 60.;
+/// How far to the side a sound can be panned, 0.5 would be all the way.
+const MAX_PANNING: f32 = 0.4;
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_channel::<MusicChannel>()
+            .add_audio_channel::<SfxChannel>()
+            .add_audio_channel::<UiChannel>()
+            .add_audio_channel::<IntensityChannel>()
+            .add_audio_channel::<SpatialChannel>()
+            .add_event::<DuckMusic>()
+            .init_resource::<MusicDuck>()
+            .init_resource::<MusicIntensity>()
+            .add_systems((duck_music, apply_volumes).chain())
+            .add_system(play_click_sound.run_if(resource_exists::<AudioAssets>()))
+            .add_system(mute_spatial_channel.on_startup())
+            .add_systems(
+                (play_ambient_sounds, update_spatial_sounds)
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(stop_spatial_sounds.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+#[derive(Resource)]
+pub struct MusicChannel;
+
+#[derive(Resource)]
+pub struct SfxChannel;
+
+#[derive(Resource)]
+pub struct UiChannel;
+
+#[derive(Resource)]
+pub struct IntensityChannel;
+
+/// Its volume is set per instance, from the distance to the [`SoundListener`].
+#[derive(Resource)]
+pub struct SpatialChannel;
+
+#[derive(SystemParam)]
+pub struct Sfx<'w> {
+    pub assets: Res<'w, AudioAssets>,
+    pub channel: Res<'w, AudioChannel<SfxChannel>>,
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MusicIntensity(pub bool);
+
+/// Turns the music down for a moment, so a jingle can be heard over it.
+#[derive(Clone, Copy, Debug)]
+pub struct DuckMusic;
+
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct SoundListener;
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct SoundEmitter {
+    instances: Vec<Handle<AudioInstance>>,
+}
+
+impl SoundEmitter {
+    pub fn play(&mut self, channel: &AudioChannel<SpatialChannel>, sound: Handle<AudioSource>) {
+        self.instances.push(channel.play(sound).handle());
+    }
+}
+
+/// A looping sound placed in the level with an `sfx_<sound>` node,
+/// playing `audio/ambient/<sound>.wav` from where the node is.
+#[derive(Component, Clone, Debug)]
+pub struct AmbientSound {
+    pub sound: String,
+}
+
+impl AmbientSound {
+    pub fn from_name(name: &str) -> Option<Self> {
+        name_suffix(name, "sfx").map(|sound| Self {
+            sound: sound.to_string(),
+        })
+    }
+}
+
+#[derive(Resource, Default)]
+struct MusicDuck {
+    timer: Option<Timer>,
+}
+
+fn duck_music(
+    mut duck_events: EventReader<DuckMusic>,
+    mut music_duck: ResMut<MusicDuck>,
+    time: Res<Time>,
+) {
+    if !duck_events.is_empty() {
+        duck_events.clear();
+        music_duck.timer = Some(Timer::from_seconds(DUCK_TIME, TimerMode::Once));
+
+        return;
+    }
+
+    // Only mark the resource as changed when the ducking ends, so the volume isn't set every frame
+    let finished = music_duck
+        .bypass_change_detection()
+        .timer
+        .as_mut()
+        .is_some_and(|timer| timer.tick(time.raw_delta()).finished());
+
+    if finished {
+        music_duck.timer = None;
+    }
+}
+
+fn apply_volumes(
+    settings: Res<Settings>,
+    music_duck: Res<MusicDuck>,
+    music_intensity: Res<MusicIntensity>,
+    music: Res<AudioChannel<MusicChannel>>,
+    intensity: Res<AudioChannel<IntensityChannel>>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+    ui: Res<AudioChannel<UiChannel>>,
+) {
+    if !settings.is_changed() && !music_duck.is_changed() && !music_intensity.is_changed() {
+        return;
+    }
+
+    let duck = if music_duck.timer.is_some() {
+        DUCK_VOLUME
+    } else {
+        1.
+    };
+
+    music
+        .set_volume(settings.music.volume() * duck)
+        .fade_in(AudioTween::linear(VOLUME_FADE));
+    intensity
+        .set_volume(if music_intensity.0 {
+            settings.music.volume() * duck
+        } else {
+            0.
+        })
+        .fade_in(AudioTween::linear(INTENSITY_FADE));
+    sfx.set_volume(settings.sfx.volume());
+    ui.set_volume(settings.ui.volume());
+}
+
+fn play_click_sound(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    audio_assets: Res<AudioAssets>,
+    ui: Res<AudioChannel<UiChannel>>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            ui.play(audio_assets.click.clone());
+        }
+    }
+}
+
+/// New instances start at the channel's volume, this keeps them quiet
+/// until they have been placed relative to the listener.
+fn mute_spatial_channel(spatial: Res<AudioChannel<SpatialChannel>>) {
+    spatial.set_volume(0.);
+}
+
+fn play_ambient_sounds(
+    mut commands: Commands,
+    query: Query<(Entity, &AmbientSound), Added<AmbientSound>>,
+    spatial: Res<AudioChannel<SpatialChannel>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, ambient_sound) in query.iter() {
+        let sound = asset_server.load(format!("audio/ambient/{}.wav", ambient_sound.sound));
+
+        commands.entity(entity).insert(SoundEmitter {
+            instances: vec![spatial.play(sound).looped().handle()],
+        });
+    }
+}
+
+fn update_spatial_sounds(
+    mut emitter_query: Query<(&mut SoundEmitter, &GlobalTransform)>,
+    listener_query: Query<&GlobalTransform, With<SoundListener>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    settings: Res<Settings>,
+) {
+    let Ok(listener) = listener_query.get_single() else {
+        return;
+    };
+
+    let right = listener.right();
+
+    for (mut emitter, transform) in emitter_query.iter_mut() {
+        let offset = transform.translation() - listener.translation();
+
+        let attenuation = (1. - offset.length() / HEARING_DISTANCE).clamp(0., 1.);
+        let volume = settings.sfx.volume() * (attenuation * attenuation) as f64;
+        let panning = 0.5 + right.dot(offset.normalize_or_zero()) * MAX_PANNING;
+
+        emitter
+            .instances
+            .retain(|handle| match audio_instances.get_mut(handle) {
+                Some(instance) => {
+                    if instance.state() == PlaybackState::Stopped {
+                        return false;
+                    }
+
+                    instance.set_volume(volume, AudioTween::default());
+                    instance.set_panning(panning as f64, AudioTween::default());
+
+                    true
+                }
+                // Not playing yet
+                None => true,
+            });
+    }
+}
+
+fn stop_spatial_sounds(spatial: Res<AudioChannel<SpatialChannel>>) {
+    spatial.stop();
+}
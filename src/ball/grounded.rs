@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::Ball;
+
+/// How flat a surface has to be for the ball to stand on it, as the up component of its normal.
+const MIN_GROUND_NORMAL_Y: f32 = 0.6;
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+pub struct Grounded {
+    pub grounded: bool,
+    /// How long the ball has been off the ground, in seconds
+    pub air_time: f32,
+}
+
+pub fn update_grounded(
+    mut ball_query: Query<(Entity, &mut Grounded), With<Ball>>,
+    rapier_context: Res<RapierContext>,
+    fixed_time: Res<FixedTime>,
+) {
+    for (ball, mut grounded) in ball_query.iter_mut() {
+        grounded.grounded = rapier_context
+            .contacts_with(ball)
+            .filter(|contact_pair| contact_pair.has_any_active_contacts())
+            .any(|contact_pair| {
+                // The normal points from the first collider to the second, turn it towards the ball
+                let towards_ball = if contact_pair.collider1() == ball {
+                    -1.
+                } else {
+                    1.
+                };
+
+                contact_pair
+                    .manifolds()
+                    .any(|manifold| manifold.normal().y * towards_ball >= MIN_GROUND_NORMAL_Y)
+            });
+
+        grounded.air_time = if grounded.grounded {
+            0.
+        } else {
+            grounded.air_time + fixed_time.period.as_secs_f32()
+        };
+    }
+}
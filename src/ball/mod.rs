@@ -1,32 +1,57 @@
-use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy::{prelude::*, utils::HashSet};
+use bevy_kira_audio::{AudioChannel, AudioControl};
 use bevy_rapier3d::prelude::*;
 use rand::Rng;
 
-use crate::{actions::Actions, hole::Won, loading::AudioAssets, GameState};
+use crate::{
+    actions::{InputSource, PlayerActions},
+    audio::SfxChannel,
+    camera::{CameraControls, CameraMode},
+    hazard::CurrentHazard,
+    hole::Won,
+    hotseat::{BallLost, Hotseat},
+    loading::AudioAssets,
+    physics::GameplaySet,
+    stats::LevelStats,
+    GameState,
+};
 
-use self::ui::BallUiPlugin;
+use self::{
+    grounded::{update_grounded, Grounded},
+    profile::{AirControl, BallProfile, SelectedBall},
+    sound::BallSoundPlugin,
+    spin::{spin_axis, SpinPlugin, SPIN_ACCELERATION},
+    trajectory::TrajectoryPlugin,
+    ui::BallUiPlugin,
+};
 
-const MAX_BALL_ENERGY: f32 = 120.;
-const BALL_FORCE: f32 = 10.;
-
-mod ui;
+pub mod grounded;
+pub mod profile;
+mod sound;
+mod spin;
+mod trajectory;
+pub mod ui;
 
 pub struct BallPlugin;
 
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Ball>()
+            .register_type::<BallOwner>()
+            .register_type::<ActiveBall>()
+            .register_type::<Grounded>()
+            .init_resource::<SelectedBall>()
             .add_plugin(BallUiPlugin)
+            .add_plugin(TrajectoryPlugin)
+            .add_plugin(BallSoundPlugin)
+            .add_plugin(SpinPlugin)
             .add_systems(
-                (
-                    ball_movement,
-                    lose_velocity,
-                    lose_condition,
-                    play_knock_sound,
-                )
-                    .in_set(OnUpdate(GameState::Playing)),
-            );
+                (update_grounded, ball_movement, lose_velocity)
+                    .chain()
+                    .in_set(GameplaySet)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_systems((lose_condition, play_knock_sound).in_set(OnUpdate(GameState::Playing)));
     }
 }
 
@@ -34,8 +59,59 @@ impl Plugin for BallPlugin {
 #[reflect(Component)]
 pub struct Ball {
     energy: f32,
+    max_energy: f32,
+    thrust: f32,
+    damping: f32,
+    air_control: AirControl,
+}
+
+impl Ball {
+    /// Returns how much the energy actually changed.
+    pub fn add_energy(&mut self, amount: f32) -> f32 {
+        let previous = self.energy;
+
+        self.energy = (self.energy + amount).clamp(0., self.max_energy);
+
+        self.energy - previous
+    }
+
+    pub fn energy_fraction(&self) -> f32 {
+        if self.max_energy > 0. {
+            self.energy / self.max_energy
+        } else {
+            0.
+        }
+    }
+
+    /// Multipliers for the thrust and the energy it uses, both 1 on the ground.
+    pub fn thrust_control(&self, grounded: bool) -> (f32, f32) {
+        if grounded {
+            (1., 1.)
+        } else {
+            (
+                self.air_control.thrust_scale(),
+                self.air_control.energy_cost(),
+            )
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct BallOwner(pub usize);
+
+impl BallOwner {
+    /// Things without an owner go with whichever ball is active.
+    pub fn matches(&self, owner: Option<&BallOwner>) -> bool {
+        owner.is_none_or(|owner| owner == self)
+    }
 }
 
+/// The ball whose turn it is, or every player's ball in a split-screen race.
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+pub struct ActiveBall;
+
 #[derive(Component, Reflect, Debug, Default, Clone, Copy)]
 #[reflect(Component)]
 pub struct Wall;
@@ -46,15 +122,32 @@ pub struct BallBundle {
     pub rigidbody: RigidBody,
     pub active_events: ActiveEvents,
     pub velocity: Velocity,
+    pub external_force: ExternalForce,
+    pub external_impulse: ExternalImpulse,
     pub friction: Friction,
+    pub restitution: Restitution,
+    pub mass_properties: ColliderMassProperties,
     pub collider: Collider,
+    pub current_hazard: CurrentHazard,
+    pub grounded: Grounded,
 }
 
 impl Default for BallBundle {
     fn default() -> Self {
+        Self::from_profile(&BallProfile::default())
+    }
+}
+
+impl BallBundle {
+    /// The collider has a radius of 1, the node gets scaled by the profile's radius instead.
+    pub fn from_profile(profile: &BallProfile) -> Self {
         Self {
             ball: Ball {
-                energy: MAX_BALL_ENERGY,
+                energy: profile.energy,
+                max_energy: profile.energy,
+                thrust: profile.thrust,
+                damping: profile.damping,
+                air_control: profile.air_control,
             },
             rigidbody: RigidBody::Dynamic,
             active_events: ActiveEvents::COLLISION_EVENTS,
@@ -62,69 +155,138 @@ impl Default for BallBundle {
                 linvel: Vec3::new(0., 0., 0.),
                 angvel: Vec3::new(0., 0., 0.),
             },
-            friction: Friction::new(1.),
+            external_force: ExternalForce::default(),
+            external_impulse: ExternalImpulse::default(),
+            friction: Friction::new(profile.friction),
+            restitution: Restitution::coefficient(profile.restitution),
+            mass_properties: ColliderMassProperties::Mass(profile.mass),
             collider: Collider::ball(1.),
+            current_hazard: CurrentHazard::default(),
+            grounded: Grounded::default(),
         }
     }
 }
 
 fn ball_movement(
-    mut commands: Commands,
-    mut ball_query: Query<(Entity, &mut Ball)>,
-    camera_query: Query<&Transform, With<Camera>>,
-    actions: Res<Actions>,
-    time: Res<Time>,
+    mut ball_query: Query<
+        (
+            &mut Ball,
+            &BallOwner,
+            Option<&InputSource>,
+            &Grounded,
+            &mut ExternalImpulse,
+            &mut Velocity,
+        ),
+        With<ActiveBall>,
+    >,
+    camera_query: Query<(&Transform, Option<&BallOwner>), With<CameraControls>>,
+    player_actions: PlayerActions,
+    camera_mode: Res<CameraMode>,
+    mut stats: ResMut<LevelStats>,
+    fixed_time: Res<FixedTime>,
 ) {
-    if let Some(movement_vector) = actions.player_movement {
-        let camera_transform = camera_query.single();
+    if !camera_mode.controls_ball() {
+        return;
+    }
 
-        let forward = (camera_transform.forward() * Vec3::new(1., 0., 1.)).normalize();
-        let right = (camera_transform.right() * Vec3::new(1., 0., 1.)).normalize();
+    let delta_seconds = fixed_time.period.as_secs_f32();
 
-        let movement_vector = (right * movement_vector.x + forward * movement_vector.y).normalize();
+    for (mut ball, owner, input_source, grounded, mut external_impulse, mut velocity) in
+        ball_query.iter_mut()
+    {
+        let actions = player_actions.get(input_source);
 
-        for (entity, mut ball) in ball_query.iter_mut() {
-            if ball.energy <= 0.0 {
-                continue;
-            }
+        let Some(movement) = actions.player_movement else {
+            continue;
+        };
+
+        // Steering is relative to the camera looking at this ball
+        let Some((camera_transform, _)) = camera_query
+            .iter()
+            .find(|(_, camera_owner)| owner.matches(*camera_owner))
+        else {
+            continue;
+        };
 
-            let impulse = movement_vector * time.delta_seconds() * BALL_FORCE;
+        let (thrust_scale, energy_cost) = ball.thrust_control(grounded.grounded);
+
+        if ball.energy <= 0.0 || thrust_scale <= 0. {
+            continue;
+        }
 
-            commands.entity(entity).insert(ExternalImpulse {
-                impulse,
-                ..Default::default()
-            });
+        let impulse = movement_direction(movement, camera_transform)
+            * delta_seconds
+            * ball.thrust
+            * thrust_scale;
 
-            ball.energy -= impulse.length();
+        if actions.spin {
+            velocity.angvel += spin_axis(movement, camera_transform)
+                * SPIN_ACCELERATION
+                * delta_seconds
+                * thrust_scale;
+        } else {
+            external_impulse.impulse += impulse;
         }
+
+        let energy_used = impulse.length() * energy_cost;
+
+        ball.energy -= energy_used;
+        stats.energy_used += energy_used;
     }
 }
 
-fn lose_velocity(mut query: Query<&mut Velocity, With<Ball>>, time: Res<Time>) {
-    if let Ok(mut velocity) = query.get_single_mut() {
-        let deacceleration = 0.5 * time.delta_seconds();
+fn movement_direction(movement: Vec2, camera_transform: &Transform) -> Vec3 {
+    let forward = (camera_transform.forward() * Vec3::new(1., 0., 1.)).normalize();
+    let right = (camera_transform.right() * Vec3::new(1., 0., 1.)).normalize();
+
+    (right * movement.x + forward * movement.y).normalize()
+}
+
+fn lose_velocity(
+    mut query: Query<(&mut Velocity, &Ball, &CurrentHazard)>,
+    fixed_time: Res<FixedTime>,
+) {
+    for (mut velocity, ball, current_hazard) in query.iter_mut() {
+        let damping = current_hazard
+            .0
+            .and_then(|hazard| hazard.damping())
+            .unwrap_or(ball.damping);
+
+        // Exponential decay, so the ball slows down the same however the time is split up
+        let factor = (-damping * fixed_time.period.as_secs_f32()).exp();
 
-        velocity.linvel *= (1. - deacceleration).max(0.);
-        velocity.angvel *= (1. - deacceleration).max(0.);
+        velocity.linvel *= factor;
+        velocity.angvel *= factor;
     }
 }
 
 fn lose_condition(
-    query: Query<(&Ball, &Velocity, &Transform)>,
+    query: Query<(Entity, &Ball, &Velocity, &Transform)>,
     won: Res<Won>,
-    mut state: ResMut<NextState<GameState>>,
+    hotseat: Res<Hotseat>,
+    mut ball_lost: EventWriter<BallLost>,
     audio_assets: Res<AudioAssets>,
-    audio: Res<Audio>,
+    audio: Res<AudioChannel<SfxChannel>>,
+    mut lost: Local<HashSet<Entity>>,
 ) {
     if won.0 {
         return;
     }
 
-    if let Ok((ball, velocity, transform)) = query.get_single() {
-        if transform.translation.y <= -10.
-            || (ball.energy <= 0. && velocity.linvel.length() <= 0.05)
-        {
-            state.set(GameState::LoadLevel);
+    for (entity, ball, velocity, transform) in query.iter() {
+        // Taking turns, running out of energy only ends the player's level, see the `hotseat` module
+        let out_of_energy =
+            !hotseat.is_multiplayer() && ball.energy <= 0. && velocity.linvel.length() <= 0.05;
+
+        if transform.translation.y > -10. && !out_of_energy {
+            // Back on the level, so it can be lost again
+            lost.remove(&entity);
+            continue;
+        }
+
+        // Only once per fall, the ball stays down there until it's put back
+        if lost.insert(entity) {
+            ball_lost.send(BallLost { ball: entity });
 
             audio.play(audio_assets.lose.clone());
         }
@@ -132,12 +294,12 @@ fn lose_condition(
 }
 
 fn play_knock_sound(
-    ball_query: Query<Entity, With<Ball>>,
+    ball_query: Query<Entity, With<ActiveBall>>,
     wall_query: Query<Entity, With<Wall>>,
     wall_mesh_query: Query<&Parent, With<Handle<Mesh>>>,
     rapier_context: Res<RapierContext>,
     audio_assets: Res<AudioAssets>,
-    audio: Res<Audio>,
+    audio: Res<AudioChannel<SfxChannel>>,
 ) {
     if let Ok(ball) = ball_query.get_single() {
         for contact_pair in rapier_context.contacts_with(ball) {
@@ -157,11 +319,8 @@ fn play_knock_sound(
                 continue;
             }
 
-            println!("aaa");
-            
             for manifold in contact_pair.manifolds() {
                 for contact_point in manifold.points() {
-                    println!("{}", contact_point.impulse().abs());
                     if contact_point.impulse().abs() > 0.5 {
                         audio
                             .play(audio_assets.knock.clone())
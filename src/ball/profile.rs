@@ -0,0 +1,173 @@
+use bevy::{ecs::system::SystemParam, prelude::*, reflect::TypeUuid};
+use bevy_asset_loader::prelude::*;
+use serde::Deserialize;
+
+/// The physical properties of a ball, loaded from `balls/*.ball.ron`.
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "f0a3cda0-400f-41dd-941f-34e7b3d4c607"]
+pub struct BallProfile {
+    pub mass: f32,
+    pub radius: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    /// How much velocity the ball loses per second
+    pub damping: f32,
+    /// The maximum amount of energy, which the ball starts with
+    pub energy: f32,
+    pub thrust: f32,
+    #[serde(default)]
+    pub air_control: AirControl,
+}
+
+impl Default for BallProfile {
+    fn default() -> Self {
+        Self {
+            mass: 4.2,
+            radius: 1.,
+            restitution: 0.,
+            friction: 1.,
+            damping: 0.5,
+            energy: 120.,
+            thrust: 10.,
+            air_control: AirControl::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Reflect, Clone, Copy, Debug, PartialEq)]
+pub enum AirControl {
+    Full,
+    /// Thrust is scaled down by the given factor
+    Reduced(f32),
+    Disabled,
+    /// Full thrust, but energy is used up faster by the given factor
+    Costly(f32),
+}
+
+impl Default for AirControl {
+    fn default() -> Self {
+        AirControl::Reduced(0.5)
+    }
+}
+
+impl AirControl {
+    pub fn thrust_scale(&self) -> f32 {
+        match self {
+            AirControl::Full | AirControl::Costly(_) => 1.,
+            AirControl::Reduced(scale) => *scale,
+            AirControl::Disabled => 0.,
+        }
+    }
+
+    /// How much energy thrusting in the air uses, relative to the same thrust on the ground.
+    pub fn energy_cost(&self) -> f32 {
+        match self {
+            AirControl::Costly(cost) => *cost,
+            _ => 1.,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BallKind {
+    #[default]
+    Standard,
+    Heavy,
+    Bouncy,
+    Light,
+    Sticky,
+}
+
+impl BallKind {
+    pub fn next(&self) -> Self {
+        match self {
+            BallKind::Standard => BallKind::Heavy,
+            BallKind::Heavy => BallKind::Bouncy,
+            BallKind::Bouncy => BallKind::Light,
+            BallKind::Light => BallKind::Sticky,
+            BallKind::Sticky => BallKind::Standard,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BallKind::Standard => "Standard",
+            BallKind::Heavy => "Heavy",
+            BallKind::Bouncy => "Bouncy",
+            BallKind::Light => "Light",
+            BallKind::Sticky => "Sticky",
+        }
+    }
+}
+
+/// Levels can override it in their meta.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct SelectedBall(pub BallKind);
+
+#[derive(AssetCollection, Resource)]
+pub struct BallProfileAssets {
+    #[asset(path = "balls/standard.ball.ron")]
+    pub standard: Handle<BallProfile>,
+    #[asset(path = "balls/heavy.ball.ron")]
+    pub heavy: Handle<BallProfile>,
+    #[asset(path = "balls/bouncy.ball.ron")]
+    pub bouncy: Handle<BallProfile>,
+    #[asset(path = "balls/light.ball.ron")]
+    pub light: Handle<BallProfile>,
+    #[asset(path = "balls/sticky.ball.ron")]
+    pub sticky: Handle<BallProfile>,
+}
+
+impl BallProfileAssets {
+    pub fn get(&self, kind: BallKind) -> &Handle<BallProfile> {
+        match kind {
+            BallKind::Standard => &self.standard,
+            BallKind::Heavy => &self.heavy,
+            BallKind::Bouncy => &self.bouncy,
+            BallKind::Light => &self.light,
+            BallKind::Sticky => &self.sticky,
+        }
+    }
+}
+
+#[derive(SystemParam)]
+pub struct BallProfiles<'w> {
+    handles: Res<'w, BallProfileAssets>,
+    profiles: Res<'w, Assets<BallProfile>>,
+}
+
+impl BallProfiles<'_> {
+    pub fn get(&self, kind: BallKind) -> BallProfile {
+        self.profiles
+            .get(self.handles.get(kind))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn air_control_thrust_scale() {
+        assert_eq!(AirControl::Full.thrust_scale(), 1.);
+        assert_eq!(AirControl::Reduced(0.25).thrust_scale(), 0.25);
+        assert_eq!(AirControl::Disabled.thrust_scale(), 0.);
+        assert_eq!(AirControl::Costly(3.).thrust_scale(), 1.);
+    }
+
+    #[test]
+    fn air_control_energy_cost() {
+        assert_eq!(AirControl::Full.energy_cost(), 1.);
+        assert_eq!(AirControl::Reduced(0.25).energy_cost(), 1.);
+        assert_eq!(AirControl::Disabled.energy_cost(), 1.);
+        assert_eq!(AirControl::Costly(3.).energy_cost(), 3.);
+    }
+
+    #[test]
+    fn air_control_defaults_to_half_thrust() {
+        assert_eq!(AirControl::default(), AirControl::Reduced(0.5));
+        assert_eq!(BallProfile::default().air_control.thrust_scale(), 0.5);
+    }
+}
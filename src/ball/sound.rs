@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    actions::{InputSource, PlayerActions},
+    audio::SfxChannel,
+    camera::CameraMode,
+    loading::AudioAssets,
+    settings::Settings,
+    GameState,
+};
+
+use super::{grounded::Grounded, ActiveBall, Ball};
+
+/// At this speed the rolling sound is at full volume and its highest pitch.
+const FULL_ROLLING_SPEED: f32 = 20.;
+const THRUST_VOLUME: f64 = 0.5;
+/// Short fades between frames, so the loops don't crackle when they change.
+const LOOP_TWEEN: Duration = Duration::from_millis(50);
+
+pub struct BallSoundPlugin;
+
+impl Plugin for BallSoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BallLoops>()
+            .add_system(start_loops.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(update_loops.in_set(OnUpdate(GameState::Playing)))
+            .add_system(stop_loops.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+#[derive(Resource, Default)]
+struct BallLoops {
+    rolling: Option<Handle<AudioInstance>>,
+    thrust: Option<Handle<AudioInstance>>,
+}
+
+fn start_loops(
+    mut ball_loops: ResMut<BallLoops>,
+    audio_assets: Res<AudioAssets>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+) {
+    let play_silently =
+        |sound: &Handle<AudioSource>| sfx.play(sound.clone()).looped().with_volume(0.).handle();
+
+    ball_loops.rolling = Some(play_silently(&audio_assets.rolling));
+    ball_loops.thrust = Some(play_silently(&audio_assets.thrust));
+}
+
+/// Racing on split-screen, the loops follow whichever ball is loudest.
+fn update_loops(
+    ball_query: Query<(&Ball, &Velocity, &Grounded, Option<&InputSource>), With<ActiveBall>>,
+    ball_loops: Res<BallLoops>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    player_actions: PlayerActions,
+    camera_mode: Res<CameraMode>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    if ball_query.is_empty() {
+        return;
+    }
+
+    // Everything stands still in photo mode, so the ball shouldn't sound like it's moving
+    let playing = !time.is_paused();
+
+    let rolling = ball_query
+        .iter()
+        .filter(|(_, _, grounded, _)| grounded.grounded && playing)
+        .map(|(_, velocity, _, _)| (velocity.linvel.length() / FULL_ROLLING_SPEED).min(1.))
+        .fold(0., f32::max);
+
+    let thrusting = ball_query.iter().any(|(ball, _, grounded, input_source)| {
+        let actions = player_actions.get(input_source);
+
+        playing
+            && camera_mode.controls_ball()
+            && actions.player_movement.is_some()
+            && ball.energy > 0.
+            && ball.thrust_control(grounded.grounded).0 > 0.
+    });
+
+    let tween = || AudioTween::linear(LOOP_TWEEN);
+
+    if let Some(instance) = ball_loops
+        .rolling
+        .as_ref()
+        .and_then(|handle| audio_instances.get_mut(handle))
+    {
+        instance.set_volume(settings.sfx.volume() * rolling as f64, tween());
+        instance.set_playback_rate(0.8 + 0.6 * rolling as f64, tween());
+    }
+
+    if let Some(instance) = ball_loops
+        .thrust
+        .as_ref()
+        .and_then(|handle| audio_instances.get_mut(handle))
+    {
+        let volume = if thrusting { THRUST_VOLUME } else { 0. };
+
+        instance.set_volume(settings.sfx.volume() * volume, tween());
+    }
+}
+
+fn stop_loops(
+    mut ball_loops: ResMut<BallLoops>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    for handle in [ball_loops.rolling.take(), ball_loops.thrust.take()]
+        .into_iter()
+        .flatten()
+    {
+        if let Some(instance) = audio_instances.get_mut(&handle) {
+            instance.stop(AudioTween::linear(LOOP_TWEEN));
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier3d::prelude::*;
+
+use crate::physics::GameplaySet;
+
+use super::{
+    grounded::{update_grounded, Grounded},
+    Ball, Wall,
+};
+
+/// How fast holding the spin modifier spins the ball up, in radians per second squared.
+pub const SPIN_ACCELERATION: f32 = 30.;
+/// Scales the sideways force a spinning ball gets from the air, relative to spin times speed.
+pub const MAGNUS_COEFFICIENT: f32 = 0.008;
+/// How much of the spin turns into speed along a wall when the ball hits it.
+const WALL_SPIN_TRANSFER: f32 = 0.3;
+
+pub struct SpinPlugin;
+
+impl Plugin for SpinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (magnus_effect, spin_off_walls)
+                .in_set(GameplaySet)
+                .after(update_grounded)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+    }
+}
+
+/// Sideways input gives sidespin, forwards gives topspin.
+pub fn spin_axis(movement: Vec2, camera_transform: &Transform) -> Vec3 {
+    let right = (camera_transform.right() * Vec3::new(1., 0., 1.)).normalize();
+
+    -(Vec3::Y * movement.x + right * movement.y)
+}
+
+pub fn magnus_acceleration(angular_velocity: Vec3, linear_velocity: Vec3) -> Vec3 {
+    MAGNUS_COEFFICIENT * angular_velocity.cross(linear_velocity)
+}
+
+fn magnus_effect(
+    mut ball_query: Query<(&mut Velocity, &Grounded), With<Ball>>,
+    fixed_time: Res<FixedTime>,
+) {
+    for (mut velocity, grounded) in ball_query.iter_mut() {
+        // On the ground the friction already turns spin into movement
+        if !grounded.grounded {
+            let acceleration = magnus_acceleration(velocity.angvel, velocity.linvel);
+
+            velocity.linvel += acceleration * fixed_time.period.as_secs_f32();
+        }
+    }
+}
+
+/// Walls grip a spinning ball and push it along them.
+/// A wall the ball was already touching before the last physics step doesn't count as a hit.
+fn spin_off_walls(
+    mut ball_query: Query<(Entity, &mut Velocity, &Transform), With<Ball>>,
+    wall_query: Query<(), With<Wall>>,
+    wall_mesh_query: Query<&Parent, With<Handle<Mesh>>>,
+    rapier_context: Res<RapierContext>,
+    mut touching: Local<HashSet<(Entity, Entity)>>,
+) {
+    let mut touching_now = HashSet::default();
+
+    for (ball, mut velocity, transform) in ball_query.iter_mut() {
+        for contact_pair in rapier_context.contacts_with(ball) {
+            if !contact_pair.has_any_active_contacts() {
+                continue;
+            }
+
+            let other = if contact_pair.collider1() == ball {
+                contact_pair.collider2()
+            } else {
+                contact_pair.collider1()
+            };
+
+            let is_wall = wall_mesh_query
+                .get(other)
+                .is_ok_and(|parent| wall_query.contains(parent.get()));
+
+            if !is_wall {
+                continue;
+            }
+
+            touching_now.insert((ball, other));
+
+            if touching.contains(&(ball, other)) {
+                continue;
+            }
+
+            let Some(manifold) = contact_pair.manifolds().next() else {
+                continue;
+            };
+
+            // The normal points from the first collider to the second, turn it towards the ball
+            let normal = if contact_pair.collider1() == ball {
+                -manifold.normal()
+            } else {
+                manifold.normal()
+            };
+
+            let radius = transform.scale.x;
+            let kick = velocity.angvel.cross(normal) * radius * WALL_SPIN_TRANSFER;
+
+            velocity.linvel += kick;
+            velocity.angvel *= 1. - WALL_SPIN_TRANSFER;
+        }
+    }
+
+    *touching = touching_now;
+}
@@ -0,0 +1,288 @@
+use bevy::{
+    ecs::{query::WorldQuery, system::SystemParam},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    actions::{Actions, BurstActions},
+    booster::Booster,
+    camera::CameraControls,
+    hazard::CurrentHazard,
+    trampoline::Trampoline,
+    util::cleanup,
+    GameState,
+};
+
+use super::{
+    grounded::Grounded,
+    movement_direction,
+    spin::{magnus_acceleration, spin_axis, SPIN_ACCELERATION},
+    ActiveBall, Ball,
+};
+
+/// How far ahead the trajectory is predicted, in simulation steps.
+const STEPS: usize = 60;
+const STEP_TIME: f32 = 1. / 30.;
+const STEPS_PER_DOT: usize = 2;
+const DOT_COUNT: usize = STEPS / STEPS_PER_DOT;
+/// How many surfaces the ball can hit in a single step before the prediction gives up on it.
+const MAX_HITS_PER_STEP: usize = 2;
+
+pub struct TrajectoryPlugin;
+
+impl Plugin for TrajectoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowTrajectory>()
+            .init_resource::<Trajectory>()
+            .add_system(spawn_trajectory_dots.in_schedule(OnEnter(GameState::Playing)))
+            .add_systems(
+                (
+                    toggle_trajectory,
+                    predict_trajectory.run_if(trajectory_shown),
+                    place_trajectory_dots,
+                )
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(cleanup::<TrajectoryDot>.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct ShowTrajectory(pub bool);
+
+#[derive(Resource, Clone, Debug, Default)]
+struct Trajectory(Vec<Vec3>);
+
+#[derive(Component, Clone, Copy, Debug)]
+struct TrajectoryDot(usize);
+
+#[derive(WorldQuery)]
+struct PredictedBall {
+    entity: Entity,
+    ball: &'static Ball,
+    transform: &'static Transform,
+    velocity: &'static Velocity,
+    mass: &'static ColliderMassProperties,
+    restitution: &'static Restitution,
+    hazard: &'static CurrentHazard,
+    grounded: &'static Grounded,
+}
+
+#[derive(SystemParam)]
+struct Surfaces<'w, 's> {
+    mesh_query: Query<'w, 's, &'static Parent, With<Handle<Mesh>>>,
+    booster_query: Query<'w, 's, (&'static Booster, &'static Transform)>,
+    trampoline_query: Query<'w, 's, (&'static Trampoline, &'static GlobalTransform)>,
+}
+
+impl Surfaces<'_, '_> {
+    fn booster(&self, hit: Entity) -> Option<(&Booster, &Transform)> {
+        let parent = self.mesh_query.get(hit).ok()?;
+
+        self.booster_query
+            .get(parent.get())
+            .ok()
+            .filter(|(booster, _)| booster.enabled)
+    }
+
+    fn trampoline(&self, hit: Entity) -> Option<(&Trampoline, &GlobalTransform)> {
+        let parent = self.mesh_query.get(hit).ok()?;
+
+        self.trampoline_query
+            .get(parent.get())
+            .ok()
+            .filter(|(trampoline, _)| trampoline.enabled)
+    }
+}
+
+fn spawn_trajectory_dots(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(
+        shape::UVSphere {
+            radius: 0.15,
+            sectors: 8,
+            stacks: 4,
+        }
+        .into(),
+    );
+    let material = materials.add(StandardMaterial {
+        base_color: Color::rgba(1., 1., 1., 0.6),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    for index in 0..DOT_COUNT {
+        commands
+            .spawn(PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            })
+            .insert(TrajectoryDot(index))
+            .insert(Name::new("TrajectoryDot"));
+    }
+}
+
+fn toggle_trajectory(
+    mut burst_actions: EventReader<BurstActions>,
+    mut show_trajectory: ResMut<ShowTrajectory>,
+) {
+    for action in burst_actions.iter() {
+        if *action == BurstActions::ToggleTrajectory {
+            show_trajectory.0 = !show_trajectory.0;
+        }
+    }
+}
+
+fn trajectory_shown(show_trajectory: Res<ShowTrajectory>) -> bool {
+    show_trajectory.0
+}
+
+fn predict_trajectory(
+    ball_query: Query<PredictedBall, With<ActiveBall>>,
+    camera_query: Query<&Transform, With<CameraControls>>,
+    surfaces: Surfaces,
+    actions: Res<Actions>,
+    rapier_context: Res<RapierContext>,
+    rapier_config: Res<RapierConfiguration>,
+    mut trajectory: ResMut<Trajectory>,
+) {
+    trajectory.0.clear();
+
+    let Ok(PredictedBallItem {
+        entity,
+        ball,
+        transform,
+        velocity,
+        mass,
+        restitution,
+        hazard,
+        grounded,
+    }) = ball_query.get_single()
+    else {
+        return;
+    };
+
+    let mass = match mass {
+        ColliderMassProperties::Mass(mass) => *mass,
+        _ => 1.,
+    };
+
+    // Holding the spin modifier spins the ball instead of pushing it
+    let (thrust, spin) = match (actions.player_movement, camera_query.get_single()) {
+        (Some(movement), Ok(camera_transform)) if actions.spin => (
+            Vec3::ZERO,
+            spin_axis(movement, camera_transform) * SPIN_ACCELERATION,
+        ),
+        (Some(movement), Ok(camera_transform)) => (
+            movement_direction(movement, camera_transform) * ball.thrust / mass,
+            Vec3::ZERO,
+        ),
+        _ => (Vec3::ZERO, Vec3::ZERO),
+    };
+
+    let damping = hazard
+        .0
+        .and_then(|hazard| hazard.damping())
+        .unwrap_or(ball.damping);
+
+    let shape = Collider::ball(transform.scale.x);
+    let filter = QueryFilter::default()
+        .exclude_sensors()
+        .exclude_collider(entity);
+
+    let mut position = transform.translation;
+    let mut angular_velocity = velocity.angvel;
+    let mut velocity = velocity.linvel;
+    let mut energy = ball.energy;
+    let mut airborne = !grounded.grounded;
+
+    for _ in 0..STEPS {
+        velocity += rapier_config.gravity * STEP_TIME;
+
+        let (thrust_scale, energy_cost) = ball.thrust_control(!airborne);
+
+        if energy > 0. {
+            velocity += thrust * thrust_scale * STEP_TIME;
+            angular_velocity += spin * thrust_scale * STEP_TIME;
+            energy -= ball.thrust * thrust_scale * energy_cost * STEP_TIME;
+        }
+
+        if airborne {
+            velocity += magnus_acceleration(angular_velocity, velocity) * STEP_TIME;
+        }
+
+        velocity *= (-damping * STEP_TIME).exp();
+        angular_velocity *= (-damping * STEP_TIME).exp();
+
+        let mut remaining_time = STEP_TIME;
+        airborne = true;
+
+        for _ in 0..MAX_HITS_PER_STEP {
+            let Some((hit, toi)) = rapier_context.cast_shape(
+                position,
+                Quat::IDENTITY,
+                velocity,
+                &shape,
+                remaining_time,
+                filter,
+            ) else {
+                position += velocity * remaining_time;
+                break;
+            };
+
+            position += velocity * toi.toi;
+            remaining_time -= toi.toi;
+            airborne = false;
+
+            // The normal is undefined if the ball starts inside something, assume it's the ground
+            let normal = if toi.status == TOIStatus::Penetrating {
+                Vec3::Y
+            } else {
+                toi.normal1
+            };
+
+            let normal_speed = velocity.dot(normal);
+
+            if let Some((trampoline, transform)) = surfaces.trampoline(hit) {
+                velocity = trampoline.bounce(velocity, transform.up());
+            } else if normal_speed < 0. {
+                velocity -= normal * normal_speed * (1. + restitution.coefficient);
+            }
+
+            if let Some((booster, transform)) = surfaces.booster(hit) {
+                velocity += -transform.forward() * STEP_TIME * booster.force;
+            }
+        }
+
+        trajectory.0.push(position);
+    }
+}
+
+fn place_trajectory_dots(
+    mut dot_query: Query<(&TrajectoryDot, &mut Transform, &mut Visibility)>,
+    trajectory: Res<Trajectory>,
+    show_trajectory: Res<ShowTrajectory>,
+) {
+    for (dot, mut dot_transform, mut visibility) in dot_query.iter_mut() {
+        let point = show_trajectory
+            .0
+            .then(|| trajectory.0.get((dot.0 + 1) * STEPS_PER_DOT - 1))
+            .flatten();
+
+        match point {
+            Some(point) => {
+                dot_transform.translation = *point;
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
@@ -1,9 +1,23 @@
 use bevy::{prelude::*, ui::UiSystem};
+use bevy_rapier3d::prelude::Velocity;
 
+use crate::camera::CameraControls;
+use crate::energy::EnergyChanged;
+use crate::hazard::{CurrentHazard, Hazard};
+use crate::hotseat::Hotseat;
+use crate::loading::FontAssets;
 use crate::util::cleanup;
 use crate::GameState;
 
-use super::{Ball, MAX_BALL_ENERGY};
+use super::{grounded::Grounded, ActiveBall, Ball, BallOwner};
+
+/// The spin at which the dot of the spin indicator reaches the edge, in radians per second.
+const SPIN_DISPLAY_MAX: f32 = 30.;
+/// Size of the spin indicator and its dot, in pixels.
+const SPIN_INDICATOR_SIZE: f32 = 50.;
+const SPIN_DOT_SIZE: f32 = 10.;
+/// How long the ball has to be off the ground before it shows as airborne, so small bounces don't flicker.
+const AIRBORNE_DISPLAY_DELAY: f32 = 0.25;
 
 pub struct BallUiPlugin;
 
@@ -11,66 +25,340 @@ impl Plugin for BallUiPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<BallUi>()
             .register_type::<BallUiBar>()
-            .add_system(setup_ball_ui.in_schedule(OnEnter(GameState::Playing)))
-            .add_system(
-                update_ball_ui
+            .register_type::<HazardIndicator>()
+            .register_type::<SpinDot>()
+            .register_type::<AirborneIndicator>()
+            .configure_set(
+                BallUiSet
                     .run_if(in_state(GameState::Playing))
                     .in_base_set(CoreSet::PostUpdate)
                     .after(UiSystem::Stack),
             )
+            .add_system(setup_ball_ui.in_schedule(OnEnter(GameState::Playing)))
+            .add_systems(
+                (
+                    update_ball_ui,
+                    flash_ball_ui,
+                    update_hazard_indicator,
+                    update_spin_indicator,
+                    update_airborne_indicator,
+                )
+                    .in_set(BallUiSet),
+            )
             .add_system(cleanup::<BallUi>.in_schedule(OnExit(GameState::Playing)));
     }
 }
 
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+struct BallUiSet;
+
 #[derive(Component, Reflect, Debug, Default, Clone, Copy)]
 #[reflect(Component)]
-struct BallUi;
+pub struct BallUi;
 
 #[derive(Component, Reflect, Debug, Default, Clone, Copy)]
 #[reflect(Component)]
 struct BallUiBar;
 
-fn setup_ball_ui(mut commands: Commands) {
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+struct HazardIndicator;
+
+/// Shows the spin of the ball, offset sideways by sidespin and upwards by topspin.
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+struct SpinDot;
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+struct AirborneIndicator;
+
+#[derive(Component, Debug, Clone)]
+struct EnergyFlash {
+    color: Color,
+    timer: Timer,
+}
+
+impl Default for EnergyFlash {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(0.4, TimerMode::Once);
+        timer.tick(timer.duration());
+
+        Self {
+            color: Color::WHITE,
+            timer,
+        }
+    }
+}
+
+fn spawn_energy_bar(commands: &mut Commands, position: UiRect, owner: Option<BallOwner>) {
     commands
         .spawn(NodeBundle {
             style: Style {
                 size: Size::new(Val::Px(50.0), Val::Px(180.0)),
                 flex_direction: FlexDirection::ColumnReverse,
                 position_type: PositionType::Absolute,
-                position: UiRect {
+                position,
+                padding: UiRect::all(Val::Px(5.)),
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            ..default()
+        })
+        .insert(BallUi)
+        .insert(Name::new("BallUi"))
+        .with_children(|parent| {
+            let mut bar = parent.spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    ..default()
+                },
+                background_color: Color::WHITE.into(),
+                ..default()
+            });
+
+            bar.insert(BallUiBar)
+                .insert(EnergyFlash::default())
+                .insert(Name::new("BallUiBar"));
+
+            if let Some(owner) = owner {
+                bar.insert(owner);
+            }
+        });
+}
+
+fn setup_ball_ui(mut commands: Commands, font_assets: Res<FontAssets>, hotseat: Res<Hotseat>) {
+    // In split-screen each player gets an energy bar on the outer edge of their half, and nothing else
+    if hotseat.split_screen {
+        for owner in 0..hotseat.player_count {
+            let position = if owner == 0 {
+                UiRect {
+                    top: Val::Percent(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                }
+            } else {
+                UiRect {
                     top: Val::Percent(10.0),
                     right: Val::Px(10.0),
                     ..Default::default()
+                }
+            };
+
+            spawn_energy_bar(&mut commands, position, Some(BallOwner(owner)));
+        }
+
+        return;
+    }
+
+    spawn_energy_bar(
+        &mut commands,
+        UiRect {
+            top: Val::Percent(10.0),
+            right: Val::Px(10.0),
+            ..Default::default()
+        },
+        None,
+    );
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(SPIN_INDICATOR_SIZE), Val::Px(SPIN_INDICATOR_SIZE)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Percent(10.0),
+                    right: Val::Px(70.0),
+                    ..Default::default()
                 },
-                padding: UiRect::all(Val::Px(5.)),
                 ..default()
             },
             background_color: Color::BLACK.into(),
             ..default()
         })
         .insert(BallUi)
-        .insert(Name::new("BallUi"))
+        .insert(Name::new("SpinIndicator"))
         .with_children(|parent| {
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        size: Size::new(Val::Px(SPIN_DOT_SIZE), Val::Px(SPIN_DOT_SIZE)),
+                        position_type: PositionType::Absolute,
                         ..default()
                     },
                     background_color: Color::WHITE.into(),
                     ..default()
                 })
-                .insert(BallUiBar)
-                .insert(Name::new("BallUiBar"));
+                .insert(SpinDot)
+                .insert(Name::new("SpinDot"));
         });
+
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: font_assets.poppins.clone(),
+                    font_size: 32.,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(BallUi)
+        .insert(HazardIndicator)
+        .insert(Name::new("HazardIndicator"));
+
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: font_assets.poppins.clone(),
+                    font_size: 24.,
+                    color: Color::rgb(0.8, 0.9, 1.),
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(50.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(BallUi)
+        .insert(AirborneIndicator)
+        .insert(Name::new("AirborneIndicator"));
+}
+
+fn update_ball_ui(
+    ball_query: Query<(&Ball, &BallOwner), With<ActiveBall>>,
+    mut ui_query: Query<(&mut Style, Option<&BallOwner>), With<BallUiBar>>,
+) {
+    for (mut style, bar_owner) in ui_query.iter_mut() {
+        if let Some((ball, _)) = ball_query
+            .iter()
+            .find(|(_, ball_owner)| ball_owner.matches(bar_owner))
+        {
+            style.size.height = Val::Percent(ball.energy / ball.max_energy * 100.);
+        }
+    }
+}
+
+fn flash_ball_ui(
+    mut energy_changed: EventReader<EnergyChanged>,
+    mut ui_query: Query<
+        (&mut BackgroundColor, &mut EnergyFlash, Option<&BallOwner>),
+        With<BallUiBar>,
+    >,
+    ball_query: Query<&BallOwner>,
+    time: Res<Time>,
+) {
+    let changes = energy_changed
+        .iter()
+        .filter_map(|change| Some((change, ball_query.get(change.ball).ok()?)))
+        .collect::<Vec<_>>();
+
+    for (mut background_color, mut flash, bar_owner) in ui_query.iter_mut() {
+        let latest_change = changes
+            .iter()
+            .rev()
+            .find(|(_, ball_owner)| ball_owner.matches(bar_owner))
+            .map(|(change, _)| change);
+
+        if let Some(change) = latest_change {
+            flash.color = if change.amount > 0. {
+                Color::rgb(0.3, 1., 0.4)
+            } else {
+                Color::rgb(1., 0.25, 0.2)
+            };
+            flash.timer.reset();
+        }
+
+        flash.timer.tick(time.delta());
+
+        let t = flash.timer.percent();
+        let [r0, g0, b0, _] = flash.color.as_rgba_f32();
+        let [r1, g1, b1, _] = Color::WHITE.as_rgba_f32();
+
+        background_color.0 = Color::rgb(r0 + (r1 - r0) * t, g0 + (g1 - g0) * t, b0 + (b1 - b0) * t);
+    }
+}
+
+fn update_hazard_indicator(
+    ball_query: Query<
+        &CurrentHazard,
+        (
+            With<ActiveBall>,
+            Or<(Changed<CurrentHazard>, Added<ActiveBall>)>,
+        ),
+    >,
+    mut ui_query: Query<&mut Text, With<HazardIndicator>>,
+) {
+    if let Ok(current_hazard) = ball_query.get_single() {
+        let (label, color) = match current_hazard.0 {
+            Some(Hazard::Water) => ("Splash!", Color::rgb(0.3, 0.5, 1.)),
+            Some(Hazard::Sand) => ("Sand", Color::rgb(0.9, 0.75, 0.45)),
+            Some(Hazard::Ice) => ("Ice", Color::rgb(0.7, 0.95, 1.)),
+            None => ("", Color::WHITE),
+        };
+
+        for mut text in ui_query.iter_mut() {
+            text.sections[0].value = label.to_string();
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+fn update_spin_indicator(
+    ball_query: Query<&Velocity, With<ActiveBall>>,
+    camera_query: Query<&Transform, With<CameraControls>>,
+    mut ui_query: Query<&mut Style, With<SpinDot>>,
+) {
+    let (Ok(velocity), Ok(camera_transform)) = (ball_query.get_single(), camera_query.get_single())
+    else {
+        return;
+    };
+
+    // The opposite of the axes spin_axis gives for sideways and forwards input
+    let right = (camera_transform.right() * Vec3::new(1., 0., 1.)).normalize();
+    let spin = Vec2::new(velocity.angvel.dot(-Vec3::Y), velocity.angvel.dot(-right));
+    let offset = (spin / SPIN_DISPLAY_MAX).clamp_length_max(1.);
+
+    let center = (SPIN_INDICATOR_SIZE - SPIN_DOT_SIZE) / 2.;
+
+    for mut style in ui_query.iter_mut() {
+        style.position.left = Val::Px(center + offset.x * center);
+        style.position.top = Val::Px(center - offset.y * center);
+    }
 }
 
-fn update_ball_ui(ball_query: Query<&Ball>, mut ui_query: Query<&mut Style, With<BallUiBar>>) {
-    if let Ok(ball) = ball_query.get_single() {
-        let energy = ball.energy;
+fn update_airborne_indicator(
+    ball_query: Query<&Grounded, With<ActiveBall>>,
+    mut ui_query: Query<&mut Text, With<AirborneIndicator>>,
+) {
+    if let Ok(grounded) = ball_query.get_single() {
+        let label = if grounded.air_time > AIRBORNE_DISPLAY_DELAY {
+            "Airborne"
+        } else {
+            ""
+        };
 
-        for mut style in ui_query.iter_mut() {
-            style.size.height = Val::Percent(energy / MAX_BALL_ENERGY * 100.);
+        for mut text in ui_query.iter_mut() {
+            if text.sections[0].value != label {
+                text.sections[0].value = label.to_string();
+            }
         }
     }
 }
@@ -1,14 +1,18 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::{RapierContext, Velocity};
+use serde_json::Value;
 
-use crate::{ball::Ball, GameState};
+use crate::{ball::Ball, physics::GameplaySet, util::has_prefix};
 
 pub struct BoosterPlugin;
 
 impl Plugin for BoosterPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Booster>()
-            .add_system(boost.in_set(OnUpdate(GameState::Playing)));
+        app.register_type::<Booster>().add_system(
+            boost
+                .in_set(GameplaySet)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
     }
 }
 
@@ -16,11 +20,28 @@ impl Plugin for BoosterPlugin {
 #[reflect(Component)]
 pub struct Booster {
     pub force: f32,
+    pub enabled: bool,
 }
 
 impl Default for Booster {
     fn default() -> Self {
-        Self { force: 1000. }
+        Self {
+            force: 1000.,
+            enabled: true,
+        }
+    }
+}
+
+impl Booster {
+    pub fn from_name(name: &str, extras: &Value) -> Option<Self> {
+        if !has_prefix(name, "speed") {
+            return None;
+        }
+
+        Some(Self {
+            enabled: extras["enabled"].as_bool().unwrap_or(true),
+            ..default()
+        })
     }
 }
 
@@ -28,10 +49,10 @@ fn boost(
     mut ball_query: Query<(Entity, &mut Velocity), With<Ball>>,
     booster_query: Query<(&Booster, &Transform)>,
     booster_mesh_query: Query<&Parent, With<Handle<Mesh>>>,
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     rapier_context: Res<RapierContext>,
 ) {
-    if let Ok((ball, mut ball_velocity)) = ball_query.get_single_mut() {
+    for (ball, mut ball_velocity) in ball_query.iter_mut() {
         for contact_pair in rapier_context.contacts_with(ball) {
             let other = if contact_pair.collider1() == ball {
                 contact_pair.collider2()
@@ -41,8 +62,12 @@ fn boost(
 
             if let Ok(parent) = booster_mesh_query.get(other) {
                 if let Ok((booster, transform)) = booster_query.get(parent.get()) {
+                    if !booster.enabled {
+                        continue;
+                    }
+
                     let direction = -transform.forward();
-                    let boost_vector = direction * time.delta_seconds() * booster.force;
+                    let boost_vector = direction * fixed_time.period.as_secs_f32() * booster.force;
 
                     ball_velocity.linvel += boost_vector;
                 }
@@ -1,18 +1,51 @@
-use bevy::{prelude::*, window::CursorGrabMode};
+use bevy::{core_pipeline::clear_color::ClearColorConfig, prelude::*, window::CursorGrabMode};
 use bevy_rapier3d::prelude::*;
 
-use crate::{actions::Actions, ball::Ball, util::cleanup, GameState};
+use crate::{
+    actions::{BurstActions, InputSource, PlayerActions},
+    audio::SoundListener,
+    ball::{ActiveBall, BallOwner},
+    hotseat::Hotseat,
+    portal::Teleported,
+    util::{cleanup, has_prefix, name_suffix},
+    GameState,
+};
 
 // TODO: Make this a setting if there's time left.
 const SENSITIVITY: f32 = 0.2;
 
+const MIN_RADIUS: f32 = 8.;
+const MAX_RADIUS: f32 = 45.;
+/// How much the radius changes per line scrolled.
+const ZOOM_STEP: f32 = 3.;
+const CAMERA_COLLISION_RADIUS: f32 = 0.6;
+
+// Rates for exponential smoothing, higher is snappier
+const POSITION_SMOOTHING: f32 = 12.;
+const ROTATION_SMOOTHING: f32 = 16.;
+/// How fast the camera moves back out after something stopped blocking it.
+const RECOVERY_SMOOTHING: f32 = 3.;
+
+/// Almost straight down, but not quite so the ball's controls still have a forward direction.
+const OVERHEAD_PITCH: f32 = -1.45;
+const OVERHEAD_DISTANCE: f32 = 70.;
+const FREE_FLY_SPEED: f32 = 25.;
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems((setup_camera, lock_cursor).in_schedule(OnEnter(GameState::Playing)))
+        app.register_type::<CinematicCamera>()
+            .init_resource::<CameraMode>()
+            .add_systems((setup_camera, lock_cursor).in_schedule(OnEnter(GameState::Playing)))
             .add_systems(
-                (update_camera, camera_control)
+                (
+                    switch_camera_mode,
+                    follow_teleport,
+                    update_camera,
+                    free_fly,
+                    camera_control,
+                )
                     .chain()
                     .in_set(OnUpdate(GameState::Playing)),
             )
@@ -22,22 +55,138 @@ impl Plugin for CameraPlugin {
     }
 }
 
-#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect, Clone, Copy, Debug)]
 #[reflect(Component)]
-struct CameraControls {
+pub struct CameraControls {
+    /// The distance the player wants, changed by zooming
     pub radius: f32,
     pub pitch: f32,
     pub yaw: f32,
+    /// The distance the camera is actually at, shorter when something is in the way
+    pub distance: f32,
+    pub focus: Vec3,
+    /// Skips smoothing for a frame, so the camera doesn't fly through the level
+    pub snap: bool,
 }
 
-fn setup_camera(mut commands: Commands) {
-    commands
-        .spawn(Camera3dBundle::default())
-        .insert(CameraControls {
-            yaw: 0.,
-            pitch: -0.5,
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub enum CameraMode {
+    #[default]
+    Follow,
+    Overhead,
+    FreeFly,
+    Intro {
+        elapsed: f32,
+    },
+    Photo,
+}
+
+impl CameraMode {
+    pub fn controls_ball(&self) -> bool {
+        matches!(self, CameraMode::Follow | CameraMode::Overhead)
+    }
+}
+
+/// A fixed camera placed in a level, shown in order by the intro.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct CinematicCamera {
+    pub order: u32,
+}
+
+impl CinematicCamera {
+    /// Matches `cam`, `cam1`, `cam_2` and their `.001` duplicates, but not names like `camera` or `camp`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if !has_prefix(name, "cam") {
+            return None;
+        }
+
+        let order = name_suffix(name, "cam")
+            .or_else(|| name["cam".len()..].split('.').next())
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_default();
+
+        Some(Self { order })
+    }
+}
+
+impl Default for CameraControls {
+    fn default() -> Self {
+        Self {
             radius: 27.,
+            pitch: -0.5,
+            yaw: 0.,
+            distance: 27.,
+            focus: Vec3::ZERO,
+            snap: true,
+        }
+    }
+}
+
+impl CameraControls {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.)
+    }
+}
+
+fn setup_camera(
+    mut commands: Commands,
+    mut camera_mode: ResMut<CameraMode>,
+    hotseat: Res<Hotseat>,
+) {
+    *camera_mode = CameraMode::Follow;
+
+    if !hotseat.split_screen {
+        commands
+            .spawn(Camera3dBundle::default())
+            .insert(CameraControls::default())
+            .insert(SoundListener);
+
+        return;
+    }
+
+    // Every player gets a camera drawing into their half of the window, see the `split_screen` module.
+    // The HUD is drawn once on top of both by a camera of its own.
+    for owner in 0..hotseat.player_count {
+        let mut camera = commands.spawn(Camera3dBundle {
+            camera: Camera {
+                order: owner as isize,
+                ..default()
+            },
+            camera_3d: Camera3d {
+                // Clearing wipes the whole window, so only the first camera does it
+                clear_color: if owner == 0 {
+                    ClearColorConfig::Default
+                } else {
+                    ClearColorConfig::None
+                },
+                ..default()
+            },
+            ..default()
         });
+
+        camera
+            .insert(CameraControls::default())
+            .insert(BallOwner(owner))
+            .insert(UiCameraConfig { show_ui: false });
+
+        if owner == 0 {
+            camera.insert(SoundListener);
+        } else {
+            camera.insert(InputSource::Gamepad);
+        }
+    }
+
+    commands.spawn(Camera2dBundle {
+        camera: Camera {
+            order: hotseat.player_count as isize,
+            ..default()
+        },
+        camera_2d: Camera2d {
+            clear_color: ClearColorConfig::None,
+        },
+        ..default()
+    });
 }
 
 fn lock_cursor(mut windows: Query<&mut Window>) {
@@ -54,48 +203,187 @@ fn unlock_cursor(mut windows: Query<&mut Window>) {
     window.cursor.grab_mode = CursorGrabMode::None;
 }
 
-fn camera_control(mut query: Query<&mut CameraControls>, actions: Res<Actions>, time: Res<Time>) {
-    let delta = actions.camera_movement.unwrap_or(Vec2::ZERO);
-    let delta = delta * SENSITIVITY * time.delta_seconds();
+fn camera_control(
+    mut query: Query<(&mut CameraControls, Option<&InputSource>)>,
+    camera_mode: Res<CameraMode>,
+    player_actions: PlayerActions,
+    time: Res<Time>,
+) {
+    if matches!(*camera_mode, CameraMode::Intro { .. }) {
+        return;
+    }
+
+    for (mut camera_controls, input_source) in query.iter_mut() {
+        let actions = player_actions.get(input_source);
+
+        let delta = actions.camera_movement.unwrap_or(Vec2::ZERO);
+        // Raw time, so the camera can still be turned while the game is paused
+        let delta = delta * SENSITIVITY * time.raw_delta_seconds();
+
+        camera_controls.yaw -= delta.x;
+        camera_controls.pitch -= delta.y;
+
+        camera_controls.yaw %= 360.;
+        camera_controls.pitch = camera_controls.pitch.clamp(-1.1, 0.2);
+
+        // The scroll wheel changes the field of view in photo mode instead
+        if *camera_mode == CameraMode::Photo {
+            continue;
+        }
+
+        if let Some(zoom) = actions.camera_zoom {
+            camera_controls.radius =
+                (camera_controls.radius - zoom * ZOOM_STEP).clamp(MIN_RADIUS, MAX_RADIUS);
+        }
+    }
+}
+
+fn switch_camera_mode(
+    mut burst_actions: EventReader<BurstActions>,
+    mut camera_mode: ResMut<CameraMode>,
+    mut query: Query<&mut CameraControls>,
+) {
+    for action in burst_actions.iter() {
+        let next_mode = match (*action, *camera_mode) {
+            (BurstActions::CycleCameraMode, CameraMode::Follow) => CameraMode::Overhead,
+            (BurstActions::CycleCameraMode, CameraMode::Overhead) => CameraMode::Follow,
+            // Flying around freely is for building levels, players could skip through walls with it
+            #[cfg(debug_assertions)]
+            (BurstActions::ToggleFreeCamera, CameraMode::FreeFly) => CameraMode::Follow,
+            #[cfg(debug_assertions)]
+            (BurstActions::ToggleFreeCamera, CameraMode::Follow | CameraMode::Overhead) => {
+                CameraMode::FreeFly
+            }
+            _ => continue,
+        };
+
+        *camera_mode = next_mode;
+
+        for mut camera_controls in query.iter_mut() {
+            camera_controls.snap = true;
+        }
+    }
+}
+
+/// Frame rate independent factor for moving a fraction of the way towards a target.
+fn smoothing(rate: f32, delta_seconds: f32) -> f32 {
+    1. - (-rate * delta_seconds).exp()
+}
+
+/// Snaps the camera to the exit portal instead of letting it travel through the level.
+fn follow_teleport(
+    mut query: Query<(&mut CameraControls, Option<&BallOwner>)>,
+    ball_query: Query<&BallOwner>,
+    mut teleported: EventReader<Teleported>,
+) {
+    for event in teleported.iter() {
+        let Ok(ball_owner) = ball_query.get(event.ball) else {
+            continue;
+        };
 
-    let mut camera_controls = query.single_mut();
+        let (yaw, _, _) = event.rotation.to_euler(EulerRot::YXZ);
 
-    camera_controls.yaw -= delta.x;
-    camera_controls.pitch -= delta.y;
+        for (mut camera_controls, camera_owner) in query.iter_mut() {
+            if !ball_owner.matches(camera_owner) {
+                continue;
+            }
 
-    camera_controls.yaw %= 360.;
-    camera_controls.pitch = camera_controls.pitch.clamp(-1.1, 0.2);
+            camera_controls.yaw += yaw;
+            camera_controls.snap = true;
+        }
+    }
 }
 
 fn update_camera(
-    mut query: Query<(&mut Transform, &CameraControls)>,
-    target: Query<(Entity, &Transform), (With<Ball>, Without<CameraControls>)>,
+    mut query: Query<(&mut Transform, &mut CameraControls, Option<&BallOwner>)>,
+    target: Query<(Entity, &Transform, &BallOwner), (With<ActiveBall>, Without<CameraControls>)>,
+    camera_mode: Res<CameraMode>,
     rapier_context: Res<RapierContext>,
+    time: Res<Time>,
 ) {
-    if let Ok((ball, target)) = target.get_single() {
-        let (mut transform, camera_controls) = query.single_mut();
+    let (pitch_override, radius_override) = match *camera_mode {
+        CameraMode::Follow => (None, None),
+        CameraMode::Overhead => (Some(OVERHEAD_PITCH), Some(OVERHEAD_DISTANCE)),
+        CameraMode::FreeFly | CameraMode::Intro { .. } | CameraMode::Photo => return,
+    };
+
+    for (mut transform, mut camera_controls, camera_owner) in query.iter_mut() {
+        let Some((ball, target, _)) = target
+            .iter()
+            .find(|(_, _, ball_owner)| ball_owner.matches(camera_owner))
+        else {
+            continue;
+        };
 
-        transform.rotation = Quat::IDENTITY;
+        let radius = radius_override.unwrap_or(camera_controls.radius);
+        let target_rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            camera_controls.yaw,
+            pitch_override.unwrap_or(camera_controls.pitch),
+            0.,
+        );
 
-        transform.rotate_x(camera_controls.pitch);
-        transform.rotate_y(camera_controls.yaw);
+        if camera_controls.snap {
+            camera_controls.focus = target.translation;
+            transform.rotation = target_rotation;
+        } else {
+            let position_factor = smoothing(POSITION_SMOOTHING, time.delta_seconds());
+            let rotation_factor = smoothing(ROTATION_SMOOTHING, time.delta_seconds());
+
+            camera_controls.focus = camera_controls
+                .focus
+                .lerp(target.translation, position_factor);
+            transform.rotation = transform.rotation.slerp(target_rotation, rotation_factor);
+        }
 
-        let ray_direction = -transform.forward().normalize();
+        let direction = transform.back();
 
-        let camera_position = if let Some((_entity, toi)) = rapier_context.cast_ray(
-            target.translation,
-            ray_direction,
-            camera_controls.radius,
-            false,
-            QueryFilter::new().exclude_collider(ball),
-        ) {
-            let hit_point = target.translation + ray_direction * (toi - 2.);
+        // A sphere doesn't slip through gaps between thin walls the way a ray does
+        let hit = rapier_context.cast_shape(
+            camera_controls.focus,
+            Quat::IDENTITY,
+            direction,
+            &Collider::ball(CAMERA_COLLISION_RADIUS),
+            radius,
+            QueryFilter::new().exclude_sensors().exclude_collider(ball),
+        );
+        let allowed_distance = hit.map_or(radius, |(_entity, toi)| toi.toi);
 
-            hit_point
+        // Pull in right away when something is in the way, ease back out otherwise, zooming included
+        if camera_controls.snap || (hit.is_some() && allowed_distance < camera_controls.distance) {
+            camera_controls.distance = allowed_distance;
         } else {
-            target.translation + ray_direction * camera_controls.radius
-        };
+            let recovery_factor = smoothing(RECOVERY_SMOOTHING, time.delta_seconds());
+
+            camera_controls.distance +=
+                (allowed_distance - camera_controls.distance) * recovery_factor;
+        }
+
+        camera_controls.snap = false;
+
+        transform.translation = camera_controls.focus + direction * camera_controls.distance;
+    }
+}
+
+fn free_fly(
+    mut query: Query<(&mut Transform, &CameraControls, Option<&InputSource>)>,
+    camera_mode: Res<CameraMode>,
+    player_actions: PlayerActions,
+    time: Res<Time>,
+) {
+    if *camera_mode != CameraMode::FreeFly {
+        return;
+    }
+
+    for (mut transform, camera_controls, input_source) in query.iter_mut() {
+        let actions = player_actions.get(input_source);
+
+        transform.rotation = camera_controls.rotation();
+
+        if let Some(movement) = actions.player_movement {
+            let direction = transform.right() * movement.x + transform.forward() * movement.y;
 
-        transform.translation = camera_position;
+            transform.translation += direction * FREE_FLY_SPEED * time.delta_seconds();
+        }
     }
 }
@@ -1,8 +1,15 @@
-use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
-use bevy_rapier3d::prelude::Velocity;
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_kira_audio::AudioChannel;
+use bevy_rapier3d::prelude::*;
+use serde_json::Value;
 
-use crate::{loading::{AnimationAssets, AudioAssets}, GameState, ball::Ball};
+use crate::{
+    audio::{SoundEmitter, SpatialChannel},
+    ball::Ball,
+    loading::{AnimationAssets, AudioAssets},
+    util::{cleanup, extras_f32, has_prefix},
+    GameState,
+};
 
 const CLUB_FORCE: f32 = 50.;
 
@@ -10,27 +17,172 @@ pub struct ClubPlugin;
 
 impl Plugin for ClubPlugin {
     fn build(&self, app: &mut App) {
-		app.add_system(play_club_animation.in_set(OnUpdate(GameState::Playing)));
-	}
+        app.add_systems(
+            (trigger_clubs, swing_clubs)
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        // Clubs can be nested in the level, so wait for their global transform
+        .add_system(
+            spawn_club_triggers
+                .run_if(in_state(GameState::Playing))
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        )
+        .add_system(cleanup::<ClubTrigger>.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+/// A club swings when the ball enters the trigger in front of it,
+/// hitting the ball along its forward vector if it's still there at the contact frame.
+#[derive(Component, Clone, Debug)]
+pub struct Club {
+    pub force: f32,
+    /// Radius of the trigger volume
+    pub reach: f32,
+    /// Seconds from the start of the swing animation to the moment the club hits
+    pub contact_time: f32,
+    pub cooldown: f32,
+    state: ClubState,
+}
+
+#[derive(Clone, Debug, Default)]
+enum ClubState {
+    #[default]
+    Armed,
+    Swinging(Timer),
+    Cooldown(Timer),
+}
+
+impl Default for Club {
+    fn default() -> Self {
+        Self {
+            force: CLUB_FORCE,
+            reach: 3.,
+            contact_time: 0.25,
+            cooldown: 2.,
+            state: ClubState::Armed,
+        }
+    }
+}
+
+impl Club {
+    pub fn from_name(name: &str, extras: &Value) -> Option<Self> {
+        if !has_prefix(name, "club") {
+            return None;
+        }
+
+        let default = Self::default();
+
+        Some(Self {
+            force: extras_f32(extras, "force").unwrap_or(default.force),
+            reach: extras_f32(extras, "reach").unwrap_or(default.reach),
+            contact_time: extras_f32(extras, "contact_time").unwrap_or(default.contact_time),
+            cooldown: extras_f32(extras, "cooldown").unwrap_or(default.cooldown),
+            ..default
+        })
+    }
 }
 
 #[derive(Component, Clone, Copy, Debug)]
-pub struct Club;
+struct ClubTrigger {
+    club: Entity,
+}
 
-fn play_club_animation(
-    mut players: Query<(&mut AnimationPlayer, &Transform), (With<AnimationPlayer>, Added<Club>)>,
-	mut ball: Query<&mut Velocity, With<Ball>>,
+fn spawn_club_triggers(
+    mut commands: Commands,
+    query: Query<(Entity, &Club, &GlobalTransform), Added<Club>>,
+) {
+    for (entity, club, transform) in query.iter() {
+        let translation = transform.translation() + transform.forward() * club.reach;
+
+        // The trigger isn't parented to the club, so it doesn't swing along with it
+        commands
+            .spawn(TransformBundle {
+                local: Transform::from_translation(translation),
+                global: GlobalTransform::from_translation(translation),
+            })
+            .insert(Collider::ball(club.reach))
+            .insert(Sensor)
+            .insert(ClubTrigger { club: entity })
+            .insert(Name::new("ClubTrigger"));
+    }
+}
+
+fn trigger_clubs(
+    mut collision_events: EventReader<CollisionEvent>,
+    ball_query: Query<(), With<Ball>>,
+    trigger_query: Query<&ClubTrigger>,
+    mut club_query: Query<(&mut Club, &mut AnimationPlayer, &mut SoundEmitter)>,
     animations: Res<AnimationAssets>,
     audio_assets: Res<AudioAssets>,
-	audio: Res<Audio>,
+    spatial: Res<AudioChannel<SpatialChannel>>,
 ) {
-	for (mut player, transform) in &mut players {
-		let mut ball_velocity = ball.single_mut();
+    for collision in collision_events.iter() {
+        if let CollisionEvent::Started(entity0, entity1, _) = collision {
+            let other = if ball_query.contains(*entity0) {
+                *entity1
+            } else if ball_query.contains(*entity1) {
+                *entity0
+            } else {
+                continue;
+            };
+
+            if let Ok(trigger) = trigger_query.get(other) {
+                if let Ok((mut club, mut player, mut emitter)) = club_query.get_mut(trigger.club) {
+                    if let ClubState::Armed = club.state {
+                        club.state = ClubState::Swinging(Timer::from_seconds(
+                            club.contact_time,
+                            TimerMode::Once,
+                        ));
+
+                        player.start(animations.club_hit.clone_weak());
+                        emitter.play(&spatial, audio_assets.swing.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn swing_clubs(
+    mut club_query: Query<(&mut Club, &GlobalTransform, &mut SoundEmitter)>,
+    trigger_query: Query<(Entity, &ClubTrigger)>,
+    mut ball_query: Query<(Entity, &mut Velocity), With<Ball>>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+    audio_assets: Res<AudioAssets>,
+    spatial: Res<AudioChannel<SpatialChannel>>,
+) {
+    for (trigger_entity, trigger) in trigger_query.iter() {
+        let Ok((mut club, transform, mut emitter)) = club_query.get_mut(trigger.club) else {
+            continue;
+        };
+        let club = &mut *club;
+
+        match &mut club.state {
+            ClubState::Armed => {}
+            ClubState::Swinging(timer) => {
+                if !timer.tick(time.delta()).finished() {
+                    continue;
+                }
 
-		ball_velocity.linvel += transform.forward() * CLUB_FORCE;
+                for (ball, mut ball_velocity) in ball_query.iter_mut() {
+                    if rapier_context.intersection_pair(ball, trigger_entity) == Some(true) {
+                        ball_velocity.linvel += transform.forward() * club.force;
 
-		audio.play(audio_assets.knock.clone());
+                        emitter.play(&spatial, audio_assets.knock.clone());
+                    }
+                }
 
-        player.play(animations.club_hit.clone_weak());
+                club.state =
+                    ClubState::Cooldown(Timer::from_seconds(club.cooldown, TimerMode::Once));
+            }
+            ClubState::Cooldown(timer) => {
+                if timer.tick(time.delta()).finished() {
+                    club.state = ClubState::Armed;
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use bevy_kira_audio::AudioControl;
+use bevy_rapier3d::prelude::*;
+use serde_json::Value;
+
+use crate::{
+    audio::Sfx,
+    ball::Ball,
+    physics::GameplaySet,
+    stats::LevelStats,
+    util::{extras_f32, has_prefix},
+    GameState,
+};
+
+pub struct EnergyPlugin;
+
+impl Plugin for EnergyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EnergyOrb>()
+            .register_type::<DrainZone>()
+            .add_event::<EnergyChanged>()
+            .add_system(collect_orbs.in_set(OnUpdate(GameState::Playing)))
+            .add_system(
+                drain_energy
+                    .in_set(GameplaySet)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+    }
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct EnergyOrb {
+    pub amount: f32,
+}
+
+impl Default for EnergyOrb {
+    fn default() -> Self {
+        Self { amount: 40. }
+    }
+}
+
+impl EnergyOrb {
+    pub fn from_name(name: &str, extras: &Value) -> Option<Self> {
+        if !has_prefix(name, "energy") {
+            return None;
+        }
+
+        Some(Self {
+            amount: extras_f32(extras, "amount").unwrap_or(Self::default().amount),
+        })
+    }
+}
+
+/// Drains energy from the ball every second it spends inside.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct DrainZone {
+    pub rate: f32,
+}
+
+impl Default for DrainZone {
+    fn default() -> Self {
+        Self { rate: 20. }
+    }
+}
+
+impl DrainZone {
+    pub fn from_name(name: &str, extras: &Value) -> Option<Self> {
+        if !has_prefix(name, "drain") {
+            return None;
+        }
+
+        Some(Self {
+            rate: extras_f32(extras, "rate").unwrap_or(Self::default().rate),
+        })
+    }
+}
+
+#[derive(Bundle)]
+pub struct EnergyOrbBundle {
+    pub orb: EnergyOrb,
+    pub collider: Collider,
+    pub sensor: Sensor,
+}
+
+impl EnergyOrbBundle {
+    pub fn new(orb: EnergyOrb) -> Self {
+        Self {
+            orb,
+            collider: Collider::ball(1.),
+            sensor: Sensor,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct DrainZoneBundle {
+    pub drain_zone: DrainZone,
+    pub collider: Collider,
+    pub sensor: Sensor,
+}
+
+impl DrainZoneBundle {
+    pub fn new(drain_zone: DrainZone) -> Self {
+        Self {
+            drain_zone,
+            collider: Collider::cuboid(1., 1., 1.),
+            sensor: Sensor,
+        }
+    }
+}
+
+/// Sent when something other than thrusting changes the ball's energy.
+#[derive(Clone, Copy, Debug)]
+pub struct EnergyChanged {
+    pub ball: Entity,
+    pub amount: f32,
+}
+
+fn collect_orbs(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut ball_query: Query<&mut Ball>,
+    orb_query: Query<&EnergyOrb>,
+    mut stats: ResMut<LevelStats>,
+    mut energy_changed: EventWriter<EnergyChanged>,
+    sfx: Sfx,
+) {
+    for collision in collision_events.iter() {
+        if let CollisionEvent::Started(entity0, entity1, _) = collision {
+            let (ball, other) = if ball_query.contains(*entity0) {
+                (*entity0, *entity1)
+            } else if ball_query.contains(*entity1) {
+                (*entity1, *entity0)
+            } else {
+                continue;
+            };
+
+            if let Ok(orb) = orb_query.get(other) {
+                let mut ball_component = ball_query.get_mut(ball).unwrap();
+                let amount = ball_component.add_energy(orb.amount);
+
+                stats.energy_collected += amount;
+                stats.orbs_collected += 1;
+
+                energy_changed.send(EnergyChanged { ball, amount });
+                sfx.channel.play(sfx.assets.pickup.clone());
+
+                commands.entity(other).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn drain_energy(
+    mut ball_query: Query<(Entity, &mut Ball)>,
+    drain_query: Query<&DrainZone>,
+    rapier_context: Res<RapierContext>,
+    mut stats: ResMut<LevelStats>,
+    mut energy_changed: EventWriter<EnergyChanged>,
+    fixed_time: Res<FixedTime>,
+) {
+    for (ball, mut ball_component) in ball_query.iter_mut() {
+        for (collider1, collider2, intersecting) in rapier_context.intersections_with(ball) {
+            if !intersecting {
+                continue;
+            }
+
+            let other = if collider1 == ball {
+                collider2
+            } else {
+                collider1
+            };
+
+            if let Ok(drain_zone) = drain_query.get(other) {
+                let amount =
+                    ball_component.add_energy(-drain_zone.rate * fixed_time.period.as_secs_f32());
+
+                if amount != 0. {
+                    stats.energy_drained -= amount;
+
+                    energy_changed.send(EnergyChanged { ball, amount });
+                }
+            }
+        }
+    }
+}
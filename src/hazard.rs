@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy_kira_audio::{AudioChannel, AudioControl};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    audio::SfxChannel, ball::Ball, hole::Won, hotseat::BallLost, loading::AudioAssets,
+    util::has_prefix, GameState,
+};
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Hazard>()
+            .register_type::<CurrentHazard>()
+            .add_systems(
+                (apply_hazard_friction, detect_hazards, drown)
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Component, Reflect, FromReflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum Hazard {
+    #[default]
+    Water,
+    Sand,
+    /// No friction and no damping
+    Ice,
+}
+
+impl Hazard {
+    pub fn from_name(name: &str) -> Option<Self> {
+        if has_prefix(name, "water") {
+            Some(Hazard::Water)
+        } else if has_prefix(name, "sand") {
+            Some(Hazard::Sand)
+        } else if has_prefix(name, "ice") {
+            Some(Hazard::Ice)
+        } else {
+            None
+        }
+    }
+
+    /// How much velocity the ball loses per second while touching the surface.
+    pub fn damping(&self) -> Option<f32> {
+        match self {
+            Hazard::Water => None,
+            Hazard::Sand => Some(4.),
+            Hazard::Ice => Some(0.),
+        }
+    }
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct CurrentHazard(pub Option<Hazard>);
+
+fn apply_hazard_friction(
+    mut commands: Commands,
+    collider_query: Query<(Entity, &Parent), Added<Collider>>,
+    hazard_query: Query<&Hazard>,
+) {
+    for (entity, parent) in collider_query.iter() {
+        if let Ok(Hazard::Ice) = hazard_query.get(parent.get()) {
+            commands.entity(entity).insert(Friction {
+                coefficient: 0.02,
+                combine_rule: CoefficientCombineRule::Min,
+            });
+        }
+    }
+}
+
+fn detect_hazards(
+    mut ball_query: Query<(Entity, &mut CurrentHazard), With<Ball>>,
+    hazard_query: Query<&Hazard>,
+    hazard_mesh_query: Query<&Parent, With<Handle<Mesh>>>,
+    rapier_context: Res<RapierContext>,
+) {
+    for (ball, mut current_hazard) in ball_query.iter_mut() {
+        let mut hazard = None;
+
+        for contact_pair in rapier_context.contacts_with(ball) {
+            if !contact_pair.has_any_active_contacts() {
+                continue;
+            }
+
+            let other = if contact_pair.collider1() == ball {
+                contact_pair.collider2()
+            } else {
+                contact_pair.collider1()
+            };
+
+            if let Ok(parent) = hazard_mesh_query.get(other) {
+                if let Ok(other_hazard) = hazard_query.get(parent.get()) {
+                    // Water always wins, otherwise the last surface touched does
+                    if hazard != Some(Hazard::Water) {
+                        hazard = Some(*other_hazard);
+                    }
+                }
+            }
+        }
+
+        if current_hazard.0 != hazard {
+            current_hazard.0 = hazard;
+        }
+    }
+}
+
+fn drown(
+    query: Query<(Entity, &CurrentHazard), Changed<CurrentHazard>>,
+    won: Res<Won>,
+    mut ball_lost: EventWriter<BallLost>,
+    audio_assets: Res<AudioAssets>,
+    audio: Res<AudioChannel<SfxChannel>>,
+) {
+    if won.0 {
+        return;
+    }
+
+    for (ball, current_hazard) in query.iter() {
+        if current_hazard.0 == Some(Hazard::Water) {
+            ball_lost.send(BallLost { ball });
+
+            audio.play(audio_assets.splash.clone());
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy_kira_audio::AudioControl;
 use bevy_rapier3d::prelude::*;
 
 use crate::{
-    ball::Ball,
-    level::LevelIndex,
-    loading::{AudioAssets, ModelAssets},
+    audio::{DuckMusic, Sfx},
+    ball::BallOwner,
+    hotseat::{EndTurn, Finish, Hotseat},
     GameState,
 };
 
@@ -15,7 +15,11 @@ impl Plugin for HolePlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Hole>()
             .init_resource::<Won>()
-            .add_system(win_condition.in_set(OnUpdate(GameState::Playing)));
+            .add_systems(
+                (win_condition.run_if(not_won), complete_level)
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
     }
 }
 
@@ -25,29 +29,63 @@ pub struct Hole;
 #[derive(Resource, Clone, Copy, Debug, Default)]
 pub struct Won(pub bool);
 
+fn not_won(won: Res<Won>) -> bool {
+    !won.0
+}
+
 fn win_condition(
     mut collision_events: EventReader<CollisionEvent>,
-    ball_query: Query<Entity, With<Ball>>,
+    ball_query: Query<&BallOwner>,
     hole_query: Query<Entity, With<Hole>>,
     hole_mesh_query: Query<&Parent, With<Collider>>,
+    mut hotseat: ResMut<Hotseat>,
+    mut end_turn: EventWriter<EndTurn>,
+    sfx: Sfx,
+) {
+    // The level's scene takes a few frames to spawn
+    let Ok(hole) = hole_query.get_single() else {
+        return;
+    };
+
+    for collision in collision_events.iter() {
+        if let CollisionEvent::Started(entity0, entity1, _) = collision {
+            let (owner, other) = if let Ok(owner) = ball_query.get(*entity0) {
+                (owner, *entity1)
+            } else if let Ok(owner) = ball_query.get(*entity1) {
+                (owner, *entity0)
+            } else {
+                continue;
+            };
+
+            if let Ok(parent) = hole_mesh_query.get(other) {
+                let Some(player) = hotseat.players.get_mut(owner.0) else {
+                    continue;
+                };
+
+                // A ball rattling around in the cup touches it more than once
+                if parent.get() == hole && player.finish.is_none() {
+                    player.finish = Some(Finish::Holed);
+                    end_turn.send(EndTurn);
+
+                    sfx.channel.play(sfx.assets.win.clone());
+                }
+            }
+        }
+    }
+}
+
+/// The level is won once everyone is done or, in a race, as soon as the first ball is in.
+fn complete_level(
+    hotseat: Res<Hotseat>,
     mut timer: Local<Timer>,
     mut won: ResMut<Won>,
-    mut level_index: ResMut<LevelIndex>,
     mut state: ResMut<NextState<GameState>>,
     time: Res<Time>,
-    audio: Res<Audio>,
-    models: Res<ModelAssets>,
-    audio_assets: Res<AudioAssets>,
+    mut duck_music: EventWriter<DuckMusic>,
 ) {
     if won.0 {
         if timer.tick(time.delta()).just_finished() {
-            level_index.0 += 1;
-
-            if level_index.0 >= models.levels.len() {
-                state.set(GameState::Win);
-            } else {
-                state.set(GameState::LoadLevel);
-            }
+            state.set(GameState::LevelComplete);
 
             won.0 = false;
         }
@@ -55,28 +93,10 @@ fn win_condition(
         return;
     }
 
-    if let Ok(ball) = ball_query.get_single() {
-        let hole = hole_query.single();
+    if hotseat.level_won() {
+        won.0 = true;
+        *timer = Timer::from_seconds(3., TimerMode::Once);
 
-        for collision in collision_events.iter() {
-            if let CollisionEvent::Started(entity0, entity1, _) = collision {
-                let other = if *entity0 == ball {
-                    *entity1
-                } else if *entity1 == ball {
-                    *entity0
-                } else {
-                    continue;
-                };
-
-                if let Ok(parent) = hole_mesh_query.get(other) {
-                    if parent.get() == hole {
-                        won.0 = true;
-                        *timer = Timer::from_seconds(3., TimerMode::Once);
-
-                        audio.play(audio_assets.win.clone());
-                    }
-                }
-            }
-        }
+        duck_music.send(DuckMusic);
     }
 }
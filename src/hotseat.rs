@@ -0,0 +1,661 @@
+use bevy::prelude::*;
+use bevy_kira_audio::AudioControl;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    actions::Actions,
+    audio::Sfx,
+    ball::{ui::BallUi, ActiveBall, Ball, BallOwner},
+    camera::{CameraControls, CameraMode},
+    loading::FontAssets,
+    GameState,
+};
+
+pub const MAX_PLAYERS: usize = 4;
+pub const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    Color::WHITE,
+    Color::rgb(1., 0.35, 0.3),
+    Color::rgb(0.35, 0.6, 1.),
+    Color::rgb(1., 0.85, 0.25),
+];
+
+const REST_SPEED: f32 = 0.3;
+/// How long the ball has to rest after a stroke before the next player takes over, in seconds.
+const REST_TIME: f32 = 1.;
+/// A turn ends after this long even if the ball never comes to rest, like on a moving platform.
+const MAX_TURN_TIME: f32 = 20.;
+/// Strokes added for running out of energy before reaching the hole.
+const OUT_OF_ENERGY_PENALTY: u32 = 2;
+
+pub struct HotseatPlugin;
+
+impl Plugin for HotseatPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Tee>()
+            .init_resource::<Hotseat>()
+            .init_resource::<Scorecard>()
+            .init_resource::<PlayerBallAssets>()
+            .add_event::<EndTurn>()
+            .add_event::<BallLost>()
+            .add_system(clear_scorecard.in_schedule(OnExit(GameState::Menu)))
+            .add_system(reset_players.in_schedule(OnEnter(GameState::LoadLevel)))
+            // After everything that loses or holes a ball, so each turn ends exactly once
+            .configure_set(
+                HotseatSet
+                    .run_if(in_state(GameState::Playing))
+                    .in_base_set(CoreSet::PostUpdate),
+            )
+            .add_system(spawn_turn_indicator.in_schedule(OnEnter(GameState::Playing)))
+            .add_systems(
+                (
+                    start_on_tee,
+                    count_strokes,
+                    finish_turn,
+                    return_lost_balls,
+                    next_turn,
+                    update_turn_indicator,
+                )
+                    .chain()
+                    .in_set(HotseatSet),
+            )
+            .add_system(record_scores.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+struct HotseatSet;
+
+#[derive(Resource, Clone, Debug)]
+pub struct Hotseat {
+    pub player_count: usize,
+    pub split_screen: bool,
+    pub active: usize,
+    pub players: Vec<PlayerScore>,
+    turn: Turn,
+}
+
+impl Default for Hotseat {
+    fn default() -> Self {
+        Self {
+            player_count: 1,
+            split_screen: false,
+            active: 0,
+            players: vec![PlayerScore::default()],
+            turn: Turn::default(),
+        }
+    }
+}
+
+impl Hotseat {
+    pub fn is_multiplayer(&self) -> bool {
+        self.player_count > 1
+    }
+
+    pub fn takes_turns(&self) -> bool {
+        self.is_multiplayer() && !self.split_screen
+    }
+
+    pub fn cycle_players(&mut self) {
+        (self.player_count, self.split_screen) = match (self.player_count, self.split_screen) {
+            (_, true) => (1, false),
+            (count, false) if count < MAX_PLAYERS => (count + 1, false),
+            (_, false) => (2, true),
+        };
+    }
+
+    pub fn all_finished(&self) -> bool {
+        self.players.iter().all(|player| player.finish.is_some())
+    }
+
+    pub fn anyone_holed(&self) -> bool {
+        self.players
+            .iter()
+            .any(|player| player.finish == Some(Finish::Holed))
+    }
+
+    /// A race is won by the first ball in the hole, otherwise everyone has to finish first.
+    pub fn level_won(&self) -> bool {
+        self.anyone_holed() && (self.split_screen || self.all_finished())
+    }
+
+    fn next_player(&self) -> Option<usize> {
+        (1..=self.player_count)
+            .map(|offset| (self.active + offset) % self.player_count)
+            .find(|player| self.players[*player].finish.is_none())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlayerScore {
+    pub strokes: u32,
+    pub finish: Option<Finish>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Finish {
+    Holed,
+    OutOfEnergy,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Turn {
+    /// Where the ball was when the turn started, a lost ball is put back here
+    start: Vec3,
+    stroke_taken: bool,
+    elapsed: f32,
+    resting: f32,
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Scorecard {
+    pub levels: Vec<Vec<PlayerScore>>,
+}
+
+impl Scorecard {
+    pub fn total(&self, player: usize) -> u32 {
+        self.levels
+            .iter()
+            .filter_map(|scores| scores.get(player))
+            .map(|score| score.strokes)
+            .sum()
+    }
+
+    pub fn rounds_won(&self, player: usize) -> u32 {
+        self.levels
+            .iter()
+            .filter(|scores| {
+                scores.get(player).and_then(|score| score.finish) == Some(Finish::Holed)
+            })
+            .count() as u32
+    }
+
+    pub fn lines(&self, hotseat: &Hotseat) -> Vec<String> {
+        (0..hotseat.player_count)
+            .map(|player| {
+                if hotseat.split_screen {
+                    return format!(
+                        "Player {}: {} rounds won",
+                        player + 1,
+                        self.rounds_won(player)
+                    );
+                }
+
+                let strokes = self
+                    .levels
+                    .iter()
+                    .filter_map(|scores| scores.get(player))
+                    .map(|score| score.strokes.to_string())
+                    .collect::<Vec<_>>();
+
+                format!(
+                    "Player {}: {} ({} total)",
+                    player + 1,
+                    strokes.join(" "),
+                    self.total(player)
+                )
+            })
+            .collect()
+    }
+
+    /// Fewest strokes, or most rounds won in a race. `None` on a tie.
+    pub fn winner(&self, hotseat: &Hotseat) -> Option<usize> {
+        let player_count = hotseat.player_count;
+        // Lower is better either way
+        let totals = (0..player_count)
+            .map(|player| {
+                if hotseat.split_screen {
+                    -(self.rounds_won(player) as i64)
+                } else {
+                    self.total(player) as i64
+                }
+            })
+            .collect::<Vec<_>>();
+        let best = *totals.iter().min()?;
+
+        let mut leaders = (0..player_count).filter(|player| totals[*player] == best);
+        let winner = leaders.next();
+
+        if leaders.next().is_some() {
+            None
+        } else {
+            winner
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+pub struct Tee(pub Vec3);
+
+#[derive(Clone, Copy, Debug)]
+pub struct EndTurn;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BallLost {
+    pub ball: Entity,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct PlayerBallAssets {
+    pub mesh: Handle<Mesh>,
+    pub materials: Vec<Handle<StandardMaterial>>,
+}
+
+impl FromWorld for PlayerBallAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(
+            shape::UVSphere {
+                radius: 1.,
+                ..default()
+            }
+            .into(),
+        );
+
+        let mut material_assets = world.resource_mut::<Assets<StandardMaterial>>();
+        let materials = PLAYER_COLORS
+            .iter()
+            .map(|color| material_assets.add((*color).into()))
+            .collect();
+
+        Self { mesh, materials }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct TurnIndicator;
+
+fn clear_scorecard(mut scorecard: ResMut<Scorecard>) {
+    *scorecard = Scorecard::default();
+}
+
+fn reset_players(mut hotseat: ResMut<Hotseat>) {
+    hotseat.active = 0;
+    hotseat.players = vec![PlayerScore::default(); hotseat.player_count];
+    hotseat.turn = Turn::default();
+}
+
+fn spawn_turn_indicator(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    hotseat: Res<Hotseat>,
+) {
+    if !hotseat.takes_turns() {
+        return;
+    }
+
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: font_assets.poppins.clone(),
+                    font_size: 32.,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..default()
+            }),
+        )
+        .insert(BallUi)
+        .insert(TurnIndicator)
+        .insert(Name::new("TurnIndicator"));
+}
+
+fn start_on_tee(
+    mut hotseat: ResMut<Hotseat>,
+    ball_query: Query<&Tee, (With<ActiveBall>, Added<Tee>)>,
+) {
+    for tee in ball_query.iter() {
+        hotseat.turn.start = tee.0;
+    }
+}
+
+fn count_strokes(
+    mut hotseat: ResMut<Hotseat>,
+    ball_query: Query<(&Ball, &Transform), With<ActiveBall>>,
+    actions: Res<Actions>,
+    camera_mode: Res<CameraMode>,
+) {
+    if !hotseat.takes_turns()
+        || hotseat.turn.stroke_taken
+        || actions.player_movement.is_none()
+        || !camera_mode.controls_ball()
+    {
+        return;
+    }
+
+    let Ok((ball, transform)) = ball_query.get_single() else {
+        return;
+    };
+
+    if ball.energy_fraction() <= 0. {
+        return;
+    }
+
+    let active = hotseat.active;
+
+    hotseat.players[active].strokes += 1;
+    hotseat.turn.stroke_taken = true;
+    hotseat.turn.start = transform.translation;
+}
+
+fn finish_turn(
+    mut hotseat: ResMut<Hotseat>,
+    ball_query: Query<(&Ball, &Velocity), With<ActiveBall>>,
+    mut end_turn: EventWriter<EndTurn>,
+    time: Res<Time>,
+) {
+    if !hotseat.takes_turns() || !hotseat.turn.stroke_taken {
+        return;
+    }
+
+    let Ok((ball, velocity)) = ball_query.get_single() else {
+        return;
+    };
+
+    let delta_seconds = time.delta_seconds();
+    let turn = &mut hotseat.turn;
+
+    turn.elapsed += delta_seconds;
+
+    if velocity.linvel.length() < REST_SPEED {
+        turn.resting += delta_seconds;
+    } else {
+        turn.resting = 0.;
+    }
+
+    if turn.resting < REST_TIME && turn.elapsed < MAX_TURN_TIME {
+        return;
+    }
+
+    turn.stroke_taken = false;
+
+    if ball.energy_fraction() <= 0. {
+        let active = hotseat.active;
+        let player = &mut hotseat.players[active];
+
+        player.strokes += OUT_OF_ENERGY_PENALTY;
+        player.finish = Some(Finish::OutOfEnergy);
+    }
+
+    end_turn.send(EndTurn);
+}
+
+/// Alone, losing the ball restarts the level. Taking turns, it goes back to where the turn started
+/// with a penalty stroke, racing to its own tee.
+fn return_lost_balls(
+    mut ball_lost: EventReader<BallLost>,
+    mut hotseat: ResMut<Hotseat>,
+    mut ball_query: Query<(&Ball, &Tee, &mut Transform, &mut Velocity)>,
+    mut end_turn: EventWriter<EndTurn>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for event in ball_lost.iter() {
+        if !hotseat.is_multiplayer() {
+            state.set(GameState::LoadLevel);
+            continue;
+        }
+
+        let Ok((ball, tee, mut transform, mut velocity)) = ball_query.get_mut(event.ball) else {
+            continue;
+        };
+
+        *velocity = Velocity::zero();
+
+        if hotseat.split_screen {
+            transform.translation = tee.0;
+            continue;
+        }
+
+        transform.translation = hotseat.turn.start;
+
+        let active = hotseat.active;
+        let player = &mut hotseat.players[active];
+
+        player.strokes += 1;
+
+        // Without energy it could never take another stroke, so its turn would never end
+        if ball.energy_fraction() <= 0. {
+            player.strokes += OUT_OF_ENERGY_PENALTY;
+            player.finish = Some(Finish::OutOfEnergy);
+        }
+
+        hotseat.turn.stroke_taken = false;
+
+        end_turn.send(EndTurn);
+    }
+}
+
+fn next_turn(
+    mut commands: Commands,
+    mut end_turn: EventReader<EndTurn>,
+    mut hotseat: ResMut<Hotseat>,
+    mut ball_query: Query<(Entity, &BallOwner, &Transform, &mut Velocity)>,
+    mut camera_query: Query<&mut CameraControls>,
+    mut state: ResMut<NextState<GameState>>,
+    sfx: Sfx,
+) {
+    if end_turn.is_empty() {
+        return;
+    }
+
+    end_turn.clear();
+
+    if !hotseat.takes_turns() {
+        return;
+    }
+
+    for (entity, owner, _, mut velocity) in ball_query.iter_mut() {
+        if owner.0 != hotseat.active {
+            continue;
+        }
+
+        *velocity = Velocity::zero();
+
+        commands
+            .entity(entity)
+            .remove::<ActiveBall>()
+            .insert(RigidBodyDisabled);
+
+        if hotseat.players[owner.0].finish == Some(Finish::Holed) {
+            commands.entity(entity).insert(Visibility::Hidden);
+        }
+    }
+
+    let Some(next) = hotseat.next_player() else {
+        // Someone made it, so the hole module finishes the level. Otherwise everyone gets another go.
+        if !hotseat.anyone_holed() {
+            state.set(GameState::LoadLevel);
+
+            sfx.channel.play(sfx.assets.lose.clone());
+        }
+
+        return;
+    };
+
+    hotseat.active = next;
+    hotseat.turn = Turn::default();
+
+    for (entity, owner, transform, _) in ball_query.iter() {
+        if owner.0 != next {
+            continue;
+        }
+
+        hotseat.turn.start = transform.translation;
+
+        commands
+            .entity(entity)
+            .remove::<RigidBodyDisabled>()
+            .insert(ActiveBall)
+            .insert(Visibility::Inherited);
+    }
+
+    for mut camera_controls in camera_query.iter_mut() {
+        camera_controls.snap = true;
+    }
+}
+
+fn update_turn_indicator(
+    hotseat: Res<Hotseat>,
+    mut ui_query: Query<&mut Text, With<TurnIndicator>>,
+) {
+    if !hotseat.is_changed() {
+        return;
+    }
+
+    let active = hotseat.active;
+    let strokes = hotseat
+        .players
+        .get(active)
+        .map_or(0, |player| player.strokes);
+
+    for mut text in ui_query.iter_mut() {
+        text.sections[0].value = format!("Player {} - strokes: {}", active + 1, strokes);
+        text.sections[0].style.color = PLAYER_COLORS[active];
+    }
+}
+
+fn record_scores(hotseat: Res<Hotseat>, mut scorecard: ResMut<Scorecard>) {
+    if hotseat.level_won() {
+        scorecard.levels.push(hotseat.players.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotseat(player_count: usize, split_screen: bool) -> Hotseat {
+        Hotseat {
+            player_count,
+            split_screen,
+            players: vec![PlayerScore::default(); player_count],
+            ..default()
+        }
+    }
+
+    fn score(strokes: u32, finish: Option<Finish>) -> PlayerScore {
+        PlayerScore { strokes, finish }
+    }
+
+    #[test]
+    fn cycle_players_goes_through_every_mode() {
+        let mut hotseat = Hotseat::default();
+        let mut modes = vec![];
+
+        for _ in 0..MAX_PLAYERS + 1 {
+            hotseat.cycle_players();
+            modes.push((hotseat.player_count, hotseat.split_screen));
+        }
+
+        assert_eq!(
+            modes,
+            [(2, false), (3, false), (4, false), (2, true), (1, false)]
+        );
+    }
+
+    #[test]
+    fn next_player_wraps_around() {
+        let mut hotseat = hotseat(3, false);
+        hotseat.active = 2;
+
+        assert_eq!(hotseat.next_player(), Some(0));
+    }
+
+    #[test]
+    fn next_player_skips_finished_players() {
+        let mut hotseat = hotseat(4, false);
+        hotseat.active = 0;
+        hotseat.players[1].finish = Some(Finish::Holed);
+        hotseat.players[2].finish = Some(Finish::OutOfEnergy);
+
+        assert_eq!(hotseat.next_player(), Some(3));
+    }
+
+    #[test]
+    fn next_player_can_be_the_active_player() {
+        let mut hotseat = hotseat(2, false);
+        hotseat.active = 1;
+        hotseat.players[0].finish = Some(Finish::Holed);
+
+        assert_eq!(hotseat.next_player(), Some(1));
+    }
+
+    #[test]
+    fn next_player_is_none_when_everyone_finished() {
+        let mut hotseat = hotseat(2, false);
+        hotseat.players[0].finish = Some(Finish::Holed);
+        hotseat.players[1].finish = Some(Finish::OutOfEnergy);
+
+        assert_eq!(hotseat.next_player(), None);
+    }
+
+    #[test]
+    fn winner_has_fewest_strokes() {
+        let scorecard = Scorecard {
+            levels: vec![
+                vec![score(3, Some(Finish::Holed)), score(2, Some(Finish::Holed))],
+                vec![score(2, Some(Finish::Holed)), score(4, Some(Finish::Holed))],
+            ],
+        };
+
+        assert_eq!(scorecard.winner(&hotseat(2, false)), Some(0));
+    }
+
+    #[test]
+    fn winner_is_none_on_tied_strokes() {
+        let scorecard = Scorecard {
+            levels: vec![
+                vec![score(3, Some(Finish::Holed)), score(2, Some(Finish::Holed))],
+                vec![score(2, Some(Finish::Holed)), score(3, Some(Finish::Holed))],
+            ],
+        };
+
+        assert_eq!(scorecard.winner(&hotseat(2, false)), None);
+    }
+
+    #[test]
+    fn winner_ignores_ties_between_trailing_players() {
+        let scorecard = Scorecard {
+            levels: vec![vec![
+                score(2, Some(Finish::Holed)),
+                score(5, Some(Finish::Holed)),
+                score(5, Some(Finish::Holed)),
+            ]],
+        };
+
+        assert_eq!(scorecard.winner(&hotseat(3, false)), Some(0));
+    }
+
+    #[test]
+    fn race_winner_has_most_rounds_won() {
+        let scorecard = Scorecard {
+            levels: vec![
+                vec![score(0, Some(Finish::Holed)), score(0, None)],
+                vec![score(0, None), score(0, Some(Finish::Holed))],
+                vec![score(0, None), score(0, Some(Finish::Holed))],
+            ],
+        };
+
+        assert_eq!(scorecard.winner(&hotseat(2, true)), Some(1));
+    }
+
+    #[test]
+    fn race_winner_is_none_on_tied_rounds() {
+        let scorecard = Scorecard {
+            levels: vec![
+                vec![score(0, Some(Finish::Holed)), score(0, None)],
+                vec![score(0, None), score(0, Some(Finish::Holed))],
+            ],
+        };
+
+        assert_eq!(scorecard.winner(&hotseat(2, true)), None);
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball::ActiveBall,
+    camera::{CameraControls, CameraMode, CinematicCamera},
+    hole::Hole,
+    hotseat::Hotseat,
+    level::CurrentLevel,
+    loading::FontAssets,
+    util::cleanup,
+    GameState,
+};
+
+/// How long the camera takes between two points of the intro's path.
+const SEGMENT_TIME: f32 = 2.;
+/// How long each of the level's fixed cinematic cameras is shown before the flyover.
+const CINEMATIC_SHOT_TIME: f32 = 2.5;
+
+pub struct IntroPlugin;
+
+impl Plugin for IntroPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (start_intro, skip_intro, play_intro, remove_overlay)
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(cleanup::<IntroOverlay>.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct IntroOverlay;
+
+fn start_intro(
+    mut commands: Commands,
+    hole_query: Query<(), Added<Hole>>,
+    mut camera_mode: ResMut<CameraMode>,
+    current_level: CurrentLevel,
+    font_assets: Res<FontAssets>,
+    hotseat: Res<Hotseat>,
+) {
+    // There's no single camera to fly in split-screen, the race starts right away
+    if hole_query.is_empty() || hotseat.split_screen {
+        return;
+    }
+
+    *camera_mode = CameraMode::Intro { elapsed: 0. };
+
+    let meta = current_level.meta().cloned().unwrap_or_default();
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(48.),
+                    left: Val::Px(0.),
+                    right: Val::Px(0.),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(IntroOverlay)
+        .insert(Name::new("IntroOverlay"))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                meta.name,
+                TextStyle {
+                    font: font_assets.poppins.clone(),
+                    font_size: 48.,
+                    color: Color::WHITE,
+                },
+            ));
+
+            if let Some(par) = meta.par {
+                parent.spawn(TextBundle::from_section(
+                    format!("Par {:.0}s", par),
+                    TextStyle {
+                        font: font_assets.poppins.clone(),
+                        font_size: 24.,
+                        color: Color::WHITE,
+                    },
+                ));
+            }
+        });
+}
+
+fn skip_intro(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut camera_mode: ResMut<CameraMode>,
+) {
+    if !matches!(*camera_mode, CameraMode::Intro { .. }) {
+        return;
+    }
+
+    if keyboard_input.get_just_pressed().next().is_some()
+        || mouse_input.get_just_pressed().next().is_some()
+    {
+        *camera_mode = CameraMode::Intro {
+            elapsed: f32::INFINITY,
+        };
+    }
+}
+
+/// Cuts between the `cam*` shots, then flies from above the hole through them to the ball.
+fn play_intro(
+    mut camera_query: Query<(&mut Transform, &mut CameraControls)>,
+    hole_query: Query<&GlobalTransform, With<Hole>>,
+    ball_query: Query<&GlobalTransform, With<ActiveBall>>,
+    waypoint_query: Query<(&CinematicCamera, &GlobalTransform)>,
+    mut camera_mode: ResMut<CameraMode>,
+    time: Res<Time>,
+) {
+    let CameraMode::Intro { elapsed } = *camera_mode else {
+        return;
+    };
+
+    let (Ok(hole), Ok(ball)) = (hole_query.get_single(), ball_query.get_single()) else {
+        return;
+    };
+
+    let (mut transform, mut camera_controls) = camera_query.single_mut();
+
+    let offset = camera_controls.rotation() * Vec3::Z * camera_controls.radius;
+
+    let mut waypoints = waypoint_query.iter().collect::<Vec<_>>();
+    waypoints.sort_by_key(|(camera, _)| camera.order);
+
+    // Skipping sets the time to infinity, which is past every shot
+    if let Some((_, shot)) = waypoints.get((elapsed / CINEMATIC_SHOT_TIME) as usize) {
+        let (_, rotation, translation) = shot.to_scale_rotation_translation();
+
+        transform.translation = translation;
+        transform.rotation = rotation;
+
+        *camera_mode = CameraMode::Intro {
+            elapsed: elapsed + time.delta_seconds(),
+        };
+
+        return;
+    }
+
+    let flyover_elapsed = elapsed - waypoints.len() as f32 * CINEMATIC_SHOT_TIME;
+
+    let points = std::iter::once(hole.translation() + offset)
+        .chain(waypoints.iter().map(|(_, waypoint)| waypoint.translation()))
+        .chain(std::iter::once(ball.translation() + offset))
+        .collect::<Vec<_>>();
+
+    let segments = points.len() - 1;
+    let progress = flyover_elapsed / (SEGMENT_TIME * segments as f32);
+
+    if progress >= 1. {
+        *camera_mode = CameraMode::Follow;
+        camera_controls.snap = true;
+
+        return;
+    }
+
+    let eased = progress * progress * (3. - 2. * progress);
+
+    transform.translation = spline_point(&points, eased * segments as f32);
+    transform.look_at(hole.translation().lerp(ball.translation(), eased), Vec3::Y);
+
+    *camera_mode = CameraMode::Intro {
+        elapsed: elapsed + time.delta_seconds(),
+    };
+}
+
+fn remove_overlay(
+    mut commands: Commands,
+    overlay_query: Query<Entity, With<IntroOverlay>>,
+    camera_mode: Res<CameraMode>,
+) {
+    if matches!(*camera_mode, CameraMode::Intro { .. }) {
+        return;
+    }
+
+    for overlay in overlay_query.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+}
+
+/// A point on a Catmull-Rom spline through all of `points`, `t` goes from 0 to the number of segments.
+fn spline_point(points: &[Vec3], t: f32) -> Vec3 {
+    let segment = (t.floor() as usize).min(points.len() - 2);
+    let t = t - segment as f32;
+
+    let point = |index: usize| points[index.min(points.len() - 1)];
+
+    let p0 = points[segment.saturating_sub(1)];
+    let p1 = point(segment);
+    let p2 = point(segment + 1);
+    let p3 = point(segment + 2);
+
+    0.5 * (2. * p1
+        + (p2 - p0) * t
+        + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t * t
+        + (3. * p1 - p0 - 3. * p2 + p3) * t * t * t)
+}
@@ -1,15 +1,41 @@
-use bevy::prelude::*;
-use bevy_rapier3d::prelude::{Collider, ComputedColliderShape, Friction};
+use bevy::{
+    ecs::{
+        system::{EntityCommands, SystemParam},
+        world::EntityRef,
+    },
+    gltf::GltfExtras,
+    prelude::*,
+    reflect::TypeUuid,
+};
+use bevy_rapier3d::prelude::{
+    Collider, ComputedColliderShape, Friction, RigidBody, RigidBodyDisabled,
+};
 use bevy_scene_hook::{HookPlugin, HookedSceneBundle, SceneHook};
+use serde::Deserialize;
+use serde_json::Value;
 
 use crate::{
-    ball::{BallBundle, Wall},
+    actions::InputSource,
+    audio::{AmbientSound, SoundEmitter},
+    ball::{
+        profile::{BallKind, BallProfiles, SelectedBall},
+        ActiveBall, BallBundle, BallOwner, Wall,
+    },
     booster::Booster,
+    camera::CinematicCamera,
     club::Club,
+    energy::{DrainZone, DrainZoneBundle, EnergyOrb, EnergyOrbBundle},
+    hazard::Hazard,
     hole::Hole,
+    hotseat::{Hotseat, PlayerBallAssets, Tee},
     loading::ModelAssets,
+    platform::Platform,
+    portal::{Portal, PortalBundle},
+    star::StarBundle,
     trampoline::Trampoline,
-    util::cleanup,
+    util::{cleanup, parse_extras},
+    wind::{WindZone, WindZoneBundle},
+    wiring::{Door, LinkTarget, Switch},
     GameState,
 };
 
@@ -32,6 +58,43 @@ pub struct LevelIndex(pub usize);
 #[reflect(Component)]
 pub struct LevelTag;
 
+/// Information about a level that isn't part of its scene, loaded from `levels/*.level.ron`.
+#[derive(Deserialize, TypeUuid, Clone, Debug, Default)]
+#[uuid = "f539a626-88d8-476d-b4ee-dc4454513fad"]
+pub struct LevelMeta {
+    pub name: String,
+    /// Forces a ball type, ignoring the one the player picked
+    #[serde(default)]
+    pub ball: Option<BallKind>,
+    /// How many stars the player needs in total before the level can be played
+    #[serde(default)]
+    pub stars_required: u32,
+    /// Target time for finishing the level, in seconds
+    #[serde(default)]
+    pub par: Option<f32>,
+    /// Path of the level's music inside `assets`, the default soundtrack plays without one
+    #[serde(default)]
+    pub music: Option<String>,
+}
+
+#[derive(SystemParam)]
+pub struct CurrentLevel<'w> {
+    pub index: Res<'w, LevelIndex>,
+    models: Res<'w, ModelAssets>,
+    level_meta_assets: Res<'w, Assets<LevelMeta>>,
+}
+
+impl CurrentLevel<'_> {
+    pub fn meta(&self) -> Option<&LevelMeta> {
+        self.level_meta_assets
+            .get(&self.models.level_meta[self.index.0])
+    }
+
+    pub fn scene(&self) -> Handle<Scene> {
+        self.models.levels[self.index.0].clone()
+    }
+}
+
 #[derive(Component, Clone, Debug)]
 struct UpdateCollider {
     pub mesh: Handle<Mesh>,
@@ -40,55 +103,100 @@ struct UpdateCollider {
 
 fn load_level(
     mut commands: Commands,
-    models: Res<ModelAssets>,
-    level_index: Res<LevelIndex>,
+    current_level: CurrentLevel,
+    selected_ball: Res<SelectedBall>,
+    ball_profiles: BallProfiles,
+    hotseat: Res<Hotseat>,
+    player_balls: Res<PlayerBallAssets>,
     mut state: ResMut<NextState<GameState>>,
 ) {
+    let ball_kind = current_level
+        .meta()
+        .and_then(|level_meta| level_meta.ball)
+        .unwrap_or(selected_ball.0);
+
+    let ball_profile = ball_profiles.get(ball_kind);
+
+    let player_count = hotseat.player_count;
+    let split_screen = hotseat.split_screen;
+    let player_balls = player_balls.clone();
+
     commands
         .spawn(HookedSceneBundle {
             scene: SceneBundle {
-                scene: models.levels[level_index.0].clone(),
+                scene: current_level.scene(),
                 ..default()
             },
-            hook: SceneHook::new(|entity, commands| {
+            hook: SceneHook::new(move |entity, commands| {
+                let extras = parse_extras(entity.get::<GltfExtras>());
+
+                if let Some(link_target) = LinkTarget::from_extras(&extras) {
+                    commands.insert(link_target);
+                }
+
                 match entity.get::<Name>().map(|name| name.as_str()).unwrap_or("") {
                     "ball" => {
-                        commands.insert(BallBundle::default());
+                        let transform = entity
+                            .get::<Transform>()
+                            .unwrap()
+                            .with_scale(Vec3::splat(ball_profile.radius));
+
+                        commands
+                            .insert(BallBundle::from_profile(&ball_profile))
+                            .insert(transform)
+                            .insert(BallOwner(0))
+                            .insert(Tee(transform.translation))
+                            .insert(ActiveBall);
+
+                        // The other players wait on the tee, hidden and frozen until their first turn.
+                        // Racing on split-screen, the second player's ball starts right away on the gamepad.
+                        let parent = entity.get::<Parent>().map(|parent| parent.get());
+
+                        for owner in 1..player_count {
+                            let mut ball = commands.commands().spawn(PbrBundle {
+                                mesh: player_balls.mesh.clone(),
+                                material: player_balls.materials[owner].clone(),
+                                transform,
+                                visibility: if split_screen {
+                                    Visibility::Inherited
+                                } else {
+                                    Visibility::Hidden
+                                },
+                                ..default()
+                            });
+
+                            ball.insert(BallBundle::from_profile(&ball_profile))
+                                .insert(BallOwner(owner))
+                                .insert(Tee(transform.translation))
+                                .insert(Name::new(format!("ball_{}", owner + 1)));
+
+                            if split_screen {
+                                ball.insert(ActiveBall).insert(InputSource::Gamepad);
+                            } else {
+                                ball.insert(RigidBodyDisabled);
+                            }
+
+                            if let Some(parent) = parent {
+                                ball.set_parent(parent);
+                            }
+                        }
                     }
                     "hole" => {
                         commands.insert(Hole);
                     }
-                    "speed" => {
-                        commands.insert(Booster::default());
-                    }
-                    "trampoline" => {
-                        commands.insert(Trampoline::default());
-                    }
-                    "club" => {
-                        commands.insert(Club);
-                    }
                     "wall" => {
                         commands.insert(Wall);
                     }
                     name => {
-                        if name.starts_with("speed") {
-                            commands.insert(Booster::default());
-                        }
-
-                        if name.starts_with("trampoline") {
-                            commands.insert(Trampoline::default());
-                        }
-
-                        let mesh = entity.get::<Handle<Mesh>>();
-                        let parent = entity.get::<Parent>();
-
-                        if let Some(mesh) = mesh {
+                        if let Some(mesh) = entity.get::<Handle<Mesh>>() {
                             commands
                                 .insert(UpdateCollider {
                                     mesh: mesh.clone(),
-                                    parent: parent.unwrap().get(),
+                                    parent: entity.get::<Parent>().unwrap().get(),
                                 })
                                 .insert(Friction::new(1.));
+                        } else {
+                            insert_level_object(entity, name, &extras, commands);
                         }
                     }
                 }
@@ -100,6 +208,79 @@ fn load_level(
     state.set(GameState::Playing);
 }
 
+fn insert_level_object(
+    entity: &EntityRef,
+    name: &str,
+    extras: &Value,
+    commands: &mut EntityCommands,
+) {
+    if let Some(booster) = Booster::from_name(name, extras) {
+        commands.insert(booster);
+    }
+
+    if let Some(trampoline) = Trampoline::from_name(name, extras) {
+        commands.insert(trampoline).insert(SoundEmitter::default());
+    }
+
+    if let Some(club) = Club::from_name(name, extras) {
+        commands.insert(club).insert(SoundEmitter::default());
+    }
+
+    let platform = entity
+        .get::<Transform>()
+        .and_then(|transform| Platform::from_name(name, transform, extras));
+
+    if let Some((platform, path)) = platform {
+        commands
+            .insert(platform)
+            .insert(RigidBody::KinematicPositionBased);
+
+        if let Some(path) = path {
+            commands.insert(path);
+        }
+    }
+
+    if let Some(hazard) = Hazard::from_name(name) {
+        commands.insert(hazard);
+    }
+
+    if let Some(portal) = Portal::from_name(name) {
+        commands.insert(PortalBundle::new(portal));
+    }
+
+    if let Some(switch) = Switch::from_name(name, extras) {
+        commands.insert(switch);
+    }
+
+    if let Some((door, link_target)) = Door::from_name(name, extras) {
+        commands.insert(door).insert(link_target);
+    }
+
+    if let Some(orb) = EnergyOrb::from_name(name, extras) {
+        commands.insert(EnergyOrbBundle::new(orb));
+    }
+
+    if let Some(drain_zone) = DrainZone::from_name(name, extras) {
+        commands.insert(DrainZoneBundle::new(drain_zone));
+    }
+
+    if let Some(wind_zone) = WindZone::from_name(name, extras) {
+        commands.insert(WindZoneBundle::new(wind_zone));
+    }
+
+    if let Some(star) = StarBundle::from_name(name) {
+        commands.insert(star);
+    }
+
+    if let Some(camera) = CinematicCamera::from_name(name) {
+        commands.insert(camera);
+    }
+
+    if let Some(ambient_sound) = AmbientSound::from_name(name) {
+        commands.insert(ambient_sound);
+    }
+}
+
 fn update_colliders(
     mut commands: Commands,
     query: Query<(Entity, &UpdateCollider)>,
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+
+use crate::{
+    hotseat::{Hotseat, Scorecard},
+    level::{CurrentLevel, LevelIndex, LevelMeta},
+    loading::{FontAssets, ModelAssets},
+    menu::{button_colors, ButtonColors},
+    progress::Progress,
+    replay::WatchReplayButton,
+    stats::LevelStats,
+    util::cleanup,
+    GameState,
+};
+
+pub struct LevelCompletePlugin;
+
+impl Plugin for LevelCompletePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_level_complete_screen.in_schedule(OnEnter(GameState::LevelComplete)))
+            .add_systems(
+                (next_level_button, button_colors).in_set(OnUpdate(GameState::LevelComplete)),
+            )
+            .add_systems(
+                (cleanup::<Camera2d>, cleanup::<LevelCompleteScreen>)
+                    .in_schedule(OnExit(GameState::LevelComplete)),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct LevelCompleteScreen;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct NextLevelButton;
+
+fn spawn_level_complete_screen(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    button_colors: Res<ButtonColors>,
+    stats: Res<LevelStats>,
+    current_level: CurrentLevel,
+    hotseat: Res<Hotseat>,
+    scorecard: Res<Scorecard>,
+) {
+    let par = current_level.meta().and_then(|meta| meta.par);
+
+    let text_style = TextStyle {
+        font: font_assets.poppins.clone(),
+        font_size: 24.,
+        color: Color::BLACK,
+    };
+
+    let stat_lines = [
+        match par {
+            Some(par) => format!("Time: {:.1}s (par {:.0}s)", stats.time, par),
+            None => format!("Time: {:.1}s", stats.time),
+        },
+        format!("Energy used: {:.0}", stats.energy_used),
+        format!(
+            "Energy orbs: {} (+{:.0})",
+            stats.orbs_collected, stats.energy_collected
+        ),
+        format!("Energy drained: {:.0}", stats.energy_drained),
+        format!("Stars: {}/{}", stats.stars_collected, stats.stars_total),
+    ];
+
+    // With more players, the strokes or rounds won matter more than the stats
+    let score_lines = if hotseat.is_multiplayer() {
+        scorecard.lines(&hotseat)
+    } else {
+        Vec::new()
+    };
+
+    commands.spawn(Camera2dBundle::default());
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::all(Val::Percent(100.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::WHITE.into(),
+            ..default()
+        })
+        .insert(LevelCompleteScreen)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(512.), Val::Auto),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Level complete!",
+                            TextStyle {
+                                font: font_assets.poppins.clone(),
+                                font_size: 48.,
+                                color: Color::BLACK,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(32.)),
+                            ..default()
+                        }),
+                    );
+
+                    for line in stat_lines {
+                        parent.spawn(TextBundle::from_section(line, text_style.clone()));
+                    }
+
+                    for (index, line) in score_lines.into_iter().enumerate() {
+                        parent.spawn(
+                            TextBundle::from_section(line, text_style.clone()).with_style(Style {
+                                margin: UiRect::top(Val::Px(if index == 0 { 24. } else { 0. })),
+                                ..default()
+                            }),
+                        );
+                    }
+
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(48.), Val::Px(48.)),
+                                margin: UiRect::top(Val::Px(64.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        })
+                        .insert(NextLevelButton)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Continue",
+                                TextStyle {
+                                    font: font_assets.poppins.clone(),
+                                    font_size: 32.,
+                                    color: Color::WHITE,
+                                },
+                            ));
+                        });
+
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(48.), Val::Px(48.)),
+                                margin: UiRect::top(Val::Px(16.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        })
+                        .insert(WatchReplayButton)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Watch replay",
+                                TextStyle {
+                                    font: font_assets.poppins.clone(),
+                                    font_size: 32.,
+                                    color: Color::WHITE,
+                                },
+                            ));
+                        });
+                });
+        });
+}
+
+fn next_level_button(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<NextLevelButton>)>,
+    mut level_index: ResMut<LevelIndex>,
+    mut state: ResMut<NextState<GameState>>,
+    models: Res<ModelAssets>,
+    level_meta_assets: Res<Assets<LevelMeta>>,
+    progress: Res<Progress>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            level_index.0 += 1;
+
+            if level_index.0 >= models.levels.len() {
+                state.set(GameState::Win);
+            } else if level_meta_assets
+                .get(&models.level_meta[level_index.0])
+                .is_none_or(|meta| progress.is_unlocked(meta))
+            {
+                state.set(GameState::LoadLevel);
+            } else {
+                // Not enough stars for the next level yet, let the player replay something
+                state.set(GameState::Menu);
+            }
+        }
+    }
+}
@@ -1,24 +1,48 @@
+// Bevy queries with a few filters are over clippy's limit, splitting them into aliases doesn't help readability
+#![allow(clippy::type_complexity)]
+
 mod actions;
+mod audio;
 mod ball;
 mod booster;
 mod camera;
 mod club;
+mod energy;
+mod hazard;
 mod hole;
+mod hotseat;
+mod intro;
 mod level;
+mod level_complete;
 mod light;
 mod loading;
 mod loading_screen;
 mod menu;
+mod photo;
+mod physics;
+mod platform;
+mod portal;
+mod progress;
+mod replay;
 mod reset;
+mod ron_asset;
+mod save;
+mod settings;
 mod soundtrack;
+mod split_screen;
+mod star;
+mod stats;
 mod trampoline;
 mod util;
 mod win_screen;
+mod wind;
+mod wiring;
 
 use crate::actions::ActionsPlugin;
 use crate::loading::LoadingPlugin;
 use crate::menu::MenuPlugin;
 
+use audio::GameAudioPlugin;
 use ball::BallPlugin;
 use bevy::app::App;
 #[cfg(debug_assertions)]
@@ -26,21 +50,35 @@ use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_kira_audio::AudioPlugin;
-use bevy_rapier3d::{
-    prelude::{NoUserData, RapierPhysicsPlugin},
-    render::RapierDebugRenderPlugin,
-};
+use bevy_rapier3d::render::RapierDebugRenderPlugin;
 use booster::BoosterPlugin;
 use camera::CameraPlugin;
 use club::ClubPlugin;
+use energy::EnergyPlugin;
+use hazard::HazardPlugin;
 use hole::HolePlugin;
+use hotseat::HotseatPlugin;
+use intro::IntroPlugin;
 use level::LevelPlugin;
+use level_complete::LevelCompletePlugin;
 use light::LightPlugin;
 use loading_screen::LoadingScreenPlugin;
+use photo::PhotoPlugin;
+use physics::PhysicsPlugin;
+use platform::PlatformPlugin;
+use portal::PortalPlugin;
+use progress::ProgressPlugin;
+use replay::ReplayPlugin;
 use reset::ResetPlugin;
+use settings::SettingsPlugin;
 use soundtrack::SoundtrackPlugin;
+use split_screen::SplitScreenPlugin;
+use star::StarPlugin;
+use stats::StatsPlugin;
 use trampoline::TrampolinePlugin;
 use win_screen::WinScreenPlugin;
+use wind::WindPlugin;
+use wiring::WiringPlugin;
 
 // This example game uses States to separate logic
 // See https://bevy-cheatbook.github.io/programming/states.html
@@ -56,8 +94,14 @@ enum GameState {
     LoadLevel,
     // Here the menu is drawn and waiting for player interaction
     Menu,
+    // The screen after you complete a level
+    LevelComplete,
     // The screen after you complete the game
     Win,
+    // Watching the last run again
+    Replay,
+    // Changing the volume of music and sounds
+    Settings,
 }
 
 pub struct GamePlugin;
@@ -68,24 +112,41 @@ impl Plugin for GamePlugin {
             .add_plugin(LoadingPlugin)
             .add_plugin(LoadingScreenPlugin)
             .add_plugin(ActionsPlugin)
-            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .add_plugin(PhysicsPlugin)
             .add_plugin(LevelPlugin)
             .add_plugin(CameraPlugin)
+            .add_plugin(IntroPlugin)
+            .add_plugin(PhotoPlugin)
             .add_plugin(LightPlugin)
             .add_plugin(AudioPlugin)
+            .add_plugin(GameAudioPlugin)
+            .add_plugin(SettingsPlugin)
             .add_plugin(SoundtrackPlugin)
             .add_plugin(MenuPlugin)
+            .add_plugin(LevelCompletePlugin)
             .add_plugin(WinScreenPlugin)
+            .add_plugin(ReplayPlugin)
             .add_plugin(ResetPlugin)
             .add_plugin(BallPlugin)
             .add_plugin(HolePlugin)
+            .add_plugin(HotseatPlugin)
+            .add_plugin(SplitScreenPlugin)
             .add_plugin(BoosterPlugin)
             .add_plugin(TrampolinePlugin)
+            .add_plugin(PlatformPlugin)
+            .add_plugin(WindPlugin)
+            .add_plugin(PortalPlugin)
+            .add_plugin(HazardPlugin)
+            .add_plugin(WiringPlugin)
+            .add_plugin(EnergyPlugin)
+            .add_plugin(StarPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(ProgressPlugin)
             .add_plugin(ClubPlugin);
 
         #[cfg(debug_assertions)]
         {
-            app.add_plugin(FrameTimeDiagnosticsPlugin::default())
+            app.add_plugin(FrameTimeDiagnosticsPlugin)
                 .add_plugin(LogDiagnosticsPlugin::default())
                 .add_plugin(WorldInspectorPlugin::new())
                 .add_plugin(RapierDebugRenderPlugin::default());
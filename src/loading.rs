@@ -1,5 +1,8 @@
+use crate::ball::profile::{BallProfile, BallProfileAssets};
+use crate::level::LevelMeta;
+use crate::ron_asset::RonAssetPlugin;
 use crate::GameState;
-use bevy::prelude::*;
+use bevy::{gltf::Gltf, prelude::*};
 use bevy_asset_loader::prelude::*;
 use bevy_kira_audio::AudioSource;
 
@@ -10,6 +13,9 @@ pub struct LoadingPlugin;
 /// If interested, take a look at <https://bevy-cheatbook.github.io/features/assets.html>
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
+        app.add_plugin(RonAssetPlugin::<BallProfile>::new(&["ball.ron"]))
+            .add_plugin(RonAssetPlugin::<LevelMeta>::new(&["level.ron"]));
+
         app.add_loading_state(
             LoadingState::new(GameState::Loading).continue_to_state(GameState::Menu),
         )
@@ -17,6 +23,7 @@ impl Plugin for LoadingPlugin {
         .add_collection_to_loading_state::<_, AudioAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, ModelAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, AnimationAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, BallProfileAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, TextureAssets>(GameState::Loading);
     }
 }
@@ -36,12 +43,29 @@ pub struct AudioAssets {
     pub knock: Handle<AudioSource>,
     #[asset(path = "audio/music.wav")]
     pub soundtrack: Handle<AudioSource>,
+    #[asset(path = "audio/menu.wav")]
+    pub menu_music: Handle<AudioSource>,
+    /// Played over the music when the ball is low on energy
+    #[asset(path = "audio/intensity.wav")]
+    pub intensity: Handle<AudioSource>,
     #[asset(path = "audio/win.wav")]
     pub win: Handle<AudioSource>,
     #[asset(path = "audio/lose.wav")]
     pub lose: Handle<AudioSource>,
     #[asset(path = "audio/boing.ogg")]
     pub boing: Handle<AudioSource>,
+    #[asset(path = "audio/splash.wav")]
+    pub splash: Handle<AudioSource>,
+    #[asset(path = "audio/pickup.wav")]
+    pub pickup: Handle<AudioSource>,
+    #[asset(path = "audio/click.wav")]
+    pub click: Handle<AudioSource>,
+    #[asset(path = "audio/swing.wav")]
+    pub swing: Handle<AudioSource>,
+    #[asset(path = "audio/rolling.wav")]
+    pub rolling: Handle<AudioSource>,
+    #[asset(path = "audio/thrust.wav")]
+    pub thrust: Handle<AudioSource>,
 }
 
 #[derive(AssetCollection, Resource)]
@@ -63,6 +87,28 @@ pub struct ModelAssets {
         collection(typed)
     )]
     pub levels: Vec<Handle<Scene>>,
+    #[asset(
+        paths(
+            "models/levels/level_1.glb",
+            "models/levels/level_2.glb",
+            "models/levels/level_3.glb",
+            "models/levels/level_4.glb",
+            "models/levels/level_5.glb"
+        ),
+        collection(typed)
+    )]
+    pub level_gltfs: Vec<Handle<Gltf>>,
+    #[asset(
+        paths(
+            "levels/level_1.level.ron",
+            "levels/level_2.level.ron",
+            "levels/level_3.level.ron",
+            "levels/level_4.level.ron",
+            "levels/level_5.level.ron"
+        ),
+        collection(typed)
+    )]
+    pub level_meta: Vec<Handle<LevelMeta>>,
 }
 
 #[derive(AssetCollection, Resource)]
@@ -1,7 +1,11 @@
-use crate::level::LevelIndex;
-use crate::loading::{FontAssets, TextureAssets};
+use crate::ball::profile::SelectedBall;
+use crate::hotseat::Hotseat;
+use crate::level::{LevelIndex, LevelMeta};
+use crate::loading::{FontAssets, ModelAssets, TextureAssets};
+use crate::progress::Progress;
 use crate::util::cleanup;
 use crate::GameState;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
 pub struct MenuPlugin;
@@ -12,7 +16,17 @@ impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ButtonColors>()
             .add_system(setup_menu.in_schedule(OnEnter(GameState::Menu)))
-            .add_systems((play_button, button_colors).in_set(OnUpdate(GameState::Menu)))
+            .add_systems(
+                (
+                    play_button,
+                    ball_button,
+                    players_button,
+                    settings_button,
+                    level_button,
+                    button_colors,
+                )
+                    .in_set(OnUpdate(GameState::Menu)),
+            )
             .add_systems(
                 (cleanup::<Menu>, cleanup::<Camera2d>).in_schedule(OnExit(GameState::Menu)),
             );
@@ -25,6 +39,18 @@ struct Menu;
 #[derive(Component, Clone, Copy, Debug)]
 struct PlayButton;
 
+#[derive(Component, Clone, Copy, Debug)]
+struct BallButton;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct PlayersButton;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct SettingsButton;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct LevelButton(usize);
+
 #[derive(Resource)]
 pub struct ButtonColors {
     pub normal: Color,
@@ -40,12 +66,65 @@ impl Default for ButtonColors {
     }
 }
 
+#[derive(SystemParam)]
+struct LevelList<'w> {
+    models: Res<'w, ModelAssets>,
+    level_meta_assets: Res<'w, Assets<LevelMeta>>,
+    progress: Res<'w, Progress>,
+}
+
+impl LevelList<'_> {
+    fn is_unlocked(&self, index: usize) -> bool {
+        self.level_meta_assets
+            .get(&self.models.level_meta[index])
+            .is_none_or(|meta| self.progress.is_unlocked(meta))
+    }
+
+    fn labels(&self) -> Vec<(String, Color)> {
+        self.models
+            .level_meta
+            .iter()
+            .enumerate()
+            .map(|(index, handle)| {
+                let meta = self
+                    .level_meta_assets
+                    .get(handle)
+                    .cloned()
+                    .unwrap_or_default();
+                let record = self.progress.level(index);
+
+                if !self.progress.is_unlocked(&meta) {
+                    (
+                        format!("{} - needs {} stars", meta.name, meta.stars_required),
+                        Color::GRAY,
+                    )
+                } else if record.completed {
+                    (
+                        format!(
+                            "{} - {}/{} stars",
+                            meta.name, record.best_stars, record.stars_total
+                        ),
+                        Color::WHITE,
+                    )
+                } else {
+                    (meta.name, Color::WHITE)
+                }
+            })
+            .collect()
+    }
+}
+
 fn setup_menu(
     mut commands: Commands,
     font_assets: Res<FontAssets>,
     texture_assets: Res<TextureAssets>,
     button_colors: Res<ButtonColors>,
+    selected_ball: Res<SelectedBall>,
+    hotseat: Res<Hotseat>,
+    level_list: LevelList,
 ) {
+    let levels = level_list.labels();
+
     commands.spawn(Camera2dBundle::default());
     commands
         .spawn(NodeBundle {
@@ -103,6 +182,117 @@ fn setup_menu(
                                 },
                             ));
                         });
+
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(64.), Val::Px(48.)),
+                                margin: UiRect::top(Val::Px(16.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        })
+                        .insert(BallButton)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                ball_button_label(&selected_ball),
+                                TextStyle {
+                                    font: font_assets.poppins.clone(),
+                                    font_size: 32.,
+                                    color: Color::WHITE,
+                                },
+                            ));
+                        });
+
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(64.), Val::Px(48.)),
+                                margin: UiRect::top(Val::Px(16.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        })
+                        .insert(PlayersButton)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                players_button_label(&hotseat),
+                                TextStyle {
+                                    font: font_assets.poppins.clone(),
+                                    font_size: 32.,
+                                    color: Color::WHITE,
+                                },
+                            ));
+                        });
+
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(64.), Val::Px(48.)),
+                                margin: UiRect::top(Val::Px(16.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        })
+                        .insert(SettingsButton)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Settings",
+                                TextStyle {
+                                    font: font_assets.poppins.clone(),
+                                    font_size: 32.,
+                                    color: Color::WHITE,
+                                },
+                            ));
+                        });
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.), Val::Auto),
+                                margin: UiRect::top(Val::Px(32.)),
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                gap: Size::height(Val::Px(8.)),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (index, (label, color)) in levels.into_iter().enumerate() {
+                                parent
+                                    .spawn(ButtonBundle {
+                                        style: Style {
+                                            size: Size::new(Val::Percent(80.), Val::Px(36.)),
+                                            justify_content: JustifyContent::Center,
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        background_color: button_colors.normal.into(),
+                                        ..default()
+                                    })
+                                    .insert(LevelButton(index))
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(
+                                            label,
+                                            TextStyle {
+                                                font: font_assets.poppins.clone(),
+                                                font_size: 20.,
+                                                color,
+                                            },
+                                        ));
+                                    });
+                            }
+                        });
                 });
         });
 }
@@ -139,3 +329,84 @@ fn play_button(
         }
     }
 }
+
+fn ball_button_label(selected_ball: &SelectedBall) -> String {
+    format!("Ball: {}", selected_ball.name())
+}
+
+fn ball_button(
+    interaction_query: Query<(&Interaction, &Children), (Changed<Interaction>, With<BallButton>)>,
+    mut text_query: Query<&mut Text>,
+    mut selected_ball: ResMut<SelectedBall>,
+) {
+    for (interaction, children) in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            selected_ball.0 = selected_ball.next();
+
+            for child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*child) {
+                    text.sections[0].value = ball_button_label(&selected_ball);
+                }
+            }
+        }
+    }
+}
+
+fn players_button_label(hotseat: &Hotseat) -> String {
+    match hotseat.player_count {
+        1 => "Players: 1".to_string(),
+        count if hotseat.split_screen => format!("Players: {} (split-screen)", count),
+        count => format!("Players: {} (hotseat)", count),
+    }
+}
+
+/// Cycles through one to four players taking turns, then two players racing on split-screen.
+fn players_button(
+    interaction_query: Query<
+        (&Interaction, &Children),
+        (Changed<Interaction>, With<PlayersButton>),
+    >,
+    mut text_query: Query<&mut Text>,
+    mut hotseat: ResMut<Hotseat>,
+) {
+    for (interaction, children) in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            hotseat.cycle_players();
+
+            for child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*child) {
+                    text.sections[0].value = players_button_label(&hotseat);
+                }
+            }
+        }
+    }
+}
+
+fn settings_button(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<SettingsButton>)>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            state.set(GameState::Settings);
+        }
+    }
+}
+
+fn level_button(
+    interaction_query: Query<(&Interaction, &LevelButton), Changed<Interaction>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut level_index: ResMut<LevelIndex>,
+    level_list: LevelList,
+) {
+    for (interaction, level_button) in interaction_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        if level_list.is_unlocked(level_button.0) {
+            level_index.0 = level_button.0;
+            state.set(GameState::LoadLevel);
+        }
+    }
+}
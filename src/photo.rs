@@ -0,0 +1,506 @@
+use std::{
+    num::NonZeroU32,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    core_pipeline::tonemapping::Tonemapping,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_asset::RenderAssets,
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d,
+            ImageCopyBuffer, ImageDataLayout, MapMode, TextureDescriptor, TextureDimension,
+            TextureFormat, TextureUsages,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        view::ColorGrading,
+        RenderApp, RenderSet,
+    },
+};
+use bevy_rapier3d::prelude::RapierConfiguration;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{Actions, BurstActions},
+    ball::{ui::BallUi, ActiveBall},
+    camera::{CameraControls, CameraMode},
+    hotseat::Hotseat,
+    level::CurrentLevel,
+    loading::FontAssets,
+    save::read_save,
+    util::cleanup,
+    GameState,
+};
+
+const PHOTO_SETTINGS_FILE: &str = "photo.ron";
+
+/// How far the camera can get from the ball in photo mode.
+const PHOTO_RADIUS: f32 = 20.;
+const PHOTO_CAMERA_SPEED: f32 = 10.;
+const MIN_FOV: f32 = 0.3;
+const MAX_FOV: f32 = 1.7;
+/// Radians of field of view per line scrolled.
+const FOV_STEP: f32 = 0.05;
+/// Exposure stops per second while holding the key.
+const EXPOSURE_SPEED: f32 = 1.5;
+/// Frames the capture camera gets to render before its image is copied.
+const CAPTURE_FRAMES: u32 = 2;
+
+pub struct PhotoPlugin;
+
+impl Plugin for PhotoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(read_save::<PhotoSettings>(PHOTO_SETTINGS_FILE))
+            .init_resource::<ScreenshotRequests>()
+            .add_plugin(ExtractResourcePlugin::<ScreenshotRequests>::default())
+            .add_systems(
+                (
+                    toggle_photo_mode,
+                    update_photo_mode_ui,
+                    move_photo_camera,
+                    adjust_photo_camera,
+                    take_photo.run_if(photo_requested),
+                    finish_captures,
+                )
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_systems(
+                (exit_photo_mode, cleanup::<PhotoModeUi>).in_schedule(OnExit(GameState::Playing)),
+            );
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<PendingScreenshots>()
+                .add_system(copy_screenshots.in_set(RenderSet::Cleanup));
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct PhotoSettings {
+    /// Where screenshots are saved, relative to the working directory
+    pub directory: PathBuf,
+}
+
+impl Default for PhotoSettings {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("screenshots"),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct PhotoModeUi;
+
+#[derive(Component, Clone, Debug)]
+struct PhotoCapture {
+    image: Handle<Image>,
+    path: PathBuf,
+    frames: u32,
+}
+
+#[derive(Resource, ExtractResource, Clone, Debug, Default)]
+struct ScreenshotRequests(Vec<(Handle<Image>, PathBuf)>);
+
+struct PendingScreenshot {
+    buffer: Buffer,
+    ready: Arc<AtomicBool>,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: usize,
+    path: PathBuf,
+}
+
+#[derive(Resource, Default)]
+struct PendingScreenshots(Vec<PendingScreenshot>);
+
+fn toggle_photo_mode(
+    mut burst_actions: EventReader<BurstActions>,
+    mut camera_mode: ResMut<CameraMode>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut time: ResMut<Time>,
+    hotseat: Res<Hotseat>,
+) {
+    for action in burst_actions.iter() {
+        // Photos are taken through a single camera, so there are none in split-screen
+        if *action != BurstActions::TogglePhotoMode || hotseat.split_screen {
+            continue;
+        }
+
+        let entering = match *camera_mode {
+            CameraMode::Follow | CameraMode::Overhead => true,
+            CameraMode::Photo => false,
+            _ => continue,
+        };
+
+        rapier_config.physics_pipeline_active = !entering;
+
+        if entering {
+            *camera_mode = CameraMode::Photo;
+            time.pause();
+        } else {
+            *camera_mode = CameraMode::Follow;
+            time.unpause();
+        }
+    }
+}
+
+fn update_photo_mode_ui(
+    mut commands: Commands,
+    camera_mode: Res<CameraMode>,
+    mut camera_query: Query<(&mut Projection, &mut ColorGrading, &mut CameraControls)>,
+    mut hud_query: Query<&mut Visibility, With<BallUi>>,
+    photo_ui_query: Query<Entity, With<PhotoModeUi>>,
+    font_assets: Res<FontAssets>,
+    mut in_photo_mode: Local<bool>,
+) {
+    let entering = *camera_mode == CameraMode::Photo;
+
+    if entering == *in_photo_mode {
+        return;
+    }
+
+    *in_photo_mode = entering;
+
+    for mut visibility in hud_query.iter_mut() {
+        *visibility = if entering {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+
+    if entering {
+        commands
+            .spawn(
+                TextBundle::from_section(
+                    "Photo mode - Scroll: FOV, Q/E: exposure, Space: take photo, P: back",
+                    TextStyle {
+                        font: font_assets.poppins.clone(),
+                        font_size: 20.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        bottom: Val::Px(10.0),
+                        left: Val::Px(10.0),
+                        ..Default::default()
+                    },
+                    ..default()
+                }),
+            )
+            .insert(PhotoModeUi)
+            .insert(Name::new("PhotoModeUi"));
+    } else {
+        for entity in photo_ui_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        for (mut projection, mut color_grading, mut camera_controls) in camera_query.iter_mut() {
+            *projection = Projection::Perspective(PerspectiveProjection::default());
+            *color_grading = ColorGrading::default();
+            camera_controls.snap = true;
+        }
+    }
+}
+
+/// Flies the camera around like the free camera, but keeps it close to the ball.
+fn move_photo_camera(
+    mut camera_query: Query<(&mut Transform, &CameraControls)>,
+    ball_query: Query<&Transform, (With<ActiveBall>, Without<CameraControls>)>,
+    camera_mode: Res<CameraMode>,
+    actions: Res<Actions>,
+    time: Res<Time>,
+) {
+    if *camera_mode != CameraMode::Photo {
+        return;
+    }
+
+    let (Ok((mut transform, camera_controls)), Ok(ball)) =
+        (camera_query.get_single_mut(), ball_query.get_single())
+    else {
+        return;
+    };
+
+    transform.rotation = camera_controls.rotation();
+
+    if let Some(movement) = actions.player_movement {
+        let direction = transform.right() * movement.x + transform.forward() * movement.y;
+
+        transform.translation += direction * PHOTO_CAMERA_SPEED * time.raw_delta_seconds();
+    }
+
+    let offset = transform.translation - ball.translation;
+
+    transform.translation = ball.translation + offset.clamp_length_max(PHOTO_RADIUS);
+}
+
+fn adjust_photo_camera(
+    mut camera_query: Query<(&mut Projection, &mut ColorGrading), With<CameraControls>>,
+    camera_mode: Res<CameraMode>,
+    actions: Res<Actions>,
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+) {
+    if *camera_mode != CameraMode::Photo {
+        return;
+    }
+
+    for (mut projection, mut color_grading) in camera_query.iter_mut() {
+        if let (Projection::Perspective(perspective), Some(zoom)) =
+            (&mut *projection, actions.camera_zoom)
+        {
+            perspective.fov = (perspective.fov - zoom * FOV_STEP).clamp(MIN_FOV, MAX_FOV);
+        }
+
+        if keyboard_input.pressed(KeyCode::Q) {
+            color_grading.exposure -= EXPOSURE_SPEED * time.raw_delta_seconds();
+        }
+
+        if keyboard_input.pressed(KeyCode::E) {
+            color_grading.exposure += EXPOSURE_SPEED * time.raw_delta_seconds();
+        }
+    }
+}
+
+fn photo_requested(camera_mode: Res<CameraMode>, keyboard_input: Res<Input<KeyCode>>) -> bool {
+    *camera_mode == CameraMode::Photo && keyboard_input.just_pressed(KeyCode::Space)
+}
+
+fn take_photo(
+    mut commands: Commands,
+    camera_query: Query<
+        (&Transform, &Projection, &ColorGrading, &Tonemapping),
+        With<CameraControls>,
+    >,
+    windows: Query<&Window>,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<PhotoSettings>,
+    current_level: CurrentLevel,
+) {
+    let (Ok((transform, projection, color_grading, tonemapping)), Ok(window)) =
+        (camera_query.get_single(), windows.get_single())
+    else {
+        return;
+    };
+
+    let size = Extent3d {
+        width: window.physical_width(),
+        height: window.physical_height(),
+        depth_or_array_layers: 1,
+    };
+
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+
+    let image = images.add(image);
+
+    let level_name = current_level
+        .meta()
+        .map(|meta| meta.name.clone())
+        .unwrap_or_else(|| format!("level_{}", current_level.index.0 + 1));
+    let level_name = level_name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .to_lowercase();
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+
+    commands
+        .spawn(Camera3dBundle {
+            camera: Camera {
+                // Render before the main camera so this frame's photo matches what's on screen
+                order: -1,
+                target: RenderTarget::Image(image.clone()),
+                ..default()
+            },
+            transform: *transform,
+            projection: projection.clone(),
+            color_grading: *color_grading,
+            tonemapping: *tonemapping,
+            ..default()
+        })
+        .insert(UiCameraConfig { show_ui: false })
+        .insert(PhotoCapture {
+            image,
+            path: settings
+                .directory
+                .join(format!("{}_{}.png", level_name, timestamp)),
+            frames: 0,
+        })
+        .insert(Name::new("PhotoCapture"));
+}
+
+fn finish_captures(
+    mut commands: Commands,
+    mut capture_query: Query<(Entity, &mut PhotoCapture)>,
+    mut requests: ResMut<ScreenshotRequests>,
+) {
+    if !requests.0.is_empty() {
+        requests.0.clear();
+    }
+
+    for (entity, mut capture) in capture_query.iter_mut() {
+        capture.frames += 1;
+
+        if capture.frames == CAPTURE_FRAMES {
+            requests
+                .0
+                .push((capture.image.clone(), capture.path.clone()));
+        } else if capture.frames > CAPTURE_FRAMES {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn exit_photo_mode(
+    mut camera_mode: ResMut<CameraMode>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut time: ResMut<Time>,
+) {
+    if *camera_mode == CameraMode::Photo {
+        *camera_mode = CameraMode::Follow;
+    }
+
+    rapier_config.physics_pipeline_active = true;
+    time.unpause();
+}
+
+fn copy_screenshots(
+    mut requests: ResMut<ScreenshotRequests>,
+    mut pending: ResMut<PendingScreenshots>,
+    images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    for (image, path) in requests.0.drain(..) {
+        let Some(gpu_image) = images.get(&image) else {
+            continue;
+        };
+
+        let width = gpu_image.size.x as u32;
+        let height = gpu_image.size.y as u32;
+        let padded_bytes_per_row = RenderDevice::align_copy_bytes_per_row(width as usize * 4);
+
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("screenshot_buffer"),
+            size: (padded_bytes_per_row * height as usize) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("screenshot_encoder"),
+        });
+
+        encoder.copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_bytes_per_row as u32),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        render_queue.submit([encoder.finish()]);
+
+        let ready = Arc::new(AtomicBool::new(false));
+        let ready_callback = ready.clone();
+
+        buffer.slice(..).map_async(MapMode::Read, move |result| {
+            if result.is_ok() {
+                ready_callback.store(true, Ordering::Release);
+            }
+        });
+
+        pending.0.push(PendingScreenshot {
+            buffer,
+            ready,
+            width,
+            height,
+            padded_bytes_per_row,
+            path,
+        });
+    }
+
+    pending.0.retain(|screenshot| {
+        if !screenshot.ready.load(Ordering::Acquire) {
+            return true;
+        }
+
+        let row_bytes = screenshot.width as usize * 4;
+        let pixels = {
+            let data = screenshot.buffer.slice(..).get_mapped_range();
+
+            data.chunks(screenshot.padded_bytes_per_row)
+                .flat_map(|row| &row[..row_bytes])
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        screenshot.buffer.unmap();
+
+        save_png(
+            screenshot.path.clone(),
+            pixels,
+            screenshot.width,
+            screenshot.height,
+        );
+
+        false
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_png(path: PathBuf, pixels: Vec<u8>, width: u32, height: u32) {
+    std::thread::spawn(move || {
+        if let Some(directory) = path.parent() {
+            let _ = std::fs::create_dir_all(directory);
+        }
+
+        match image::save_buffer(&path, &pixels, width, height, image::ColorType::Rgba8) {
+            Ok(()) => info!("Saved photo to {}", path.display()),
+            Err(error) => error!("Couldn't save photo to {}: {}", path.display(), error),
+        }
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_png(_path: PathBuf, _pixels: Vec<u8>, _width: u32, _height: u32) {}
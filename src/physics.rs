@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::GameState;
+
+/// Length of one physics step, gameplay forces are applied once per step too.
+pub const PHYSICS_TIMESTEP: f32 = 1. / 60.;
+
+pub struct PhysicsPlugin;
+
+/// Runs Rapier on the fixed timestep, so the same inputs give the same trajectory at any frame rate.
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(
+            RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false),
+        )
+        .insert_resource(RapierConfiguration {
+            timestep_mode: TimestepMode::Fixed {
+                dt: PHYSICS_TIMESTEP,
+                substeps: 1,
+            },
+            ..default()
+        })
+        .insert_resource(FixedTime::new_from_secs(PHYSICS_TIMESTEP))
+        .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+            schedule
+                .configure_set(GameplaySet.run_if(in_state(GameState::Playing)))
+                .configure_sets(
+                    (
+                        GameplaySet,
+                        PhysicsSet::SyncBackend,
+                        PhysicsSet::SyncBackendFlush,
+                        PhysicsSet::StepSimulation,
+                        PhysicsSet::Writeback,
+                    )
+                        .chain(),
+                );
+        });
+
+        for set in [
+            PhysicsSet::SyncBackend,
+            PhysicsSet::SyncBackendFlush,
+            PhysicsSet::StepSimulation,
+            PhysicsSet::Writeback,
+        ] {
+            app.add_systems(
+                RapierPhysicsPlugin::<NoUserData>::get_systems(set.clone())
+                    .in_base_set(set)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+        }
+    }
+}
+
+/// Runs right before each physics step, use [`FixedTime::period`] instead of [`Time::delta_seconds`] in it.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GameplaySet;
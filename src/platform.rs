@@ -0,0 +1,104 @@
+use std::f32::consts::PI;
+
+use bevy::{gltf::Gltf, prelude::*};
+use serde_json::Value;
+
+use crate::{
+    level::LevelIndex,
+    loading::ModelAssets,
+    physics::GameplaySet,
+    util::{extras_f32, extras_vec3, has_prefix},
+    GameState,
+};
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Platform>()
+            .register_type::<PlatformPath>()
+            .add_system(play_platform_animations.in_set(OnUpdate(GameState::Playing)))
+            .add_system(
+                move_platforms
+                    .in_set(GameplaySet)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+    }
+}
+
+/// A kinematic platform. If it has a [`PlatformPath`] it moves back and forth along it,
+/// otherwise it loops the glTF animation named `animation`.
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component)]
+pub struct Platform {
+    pub animation: String,
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct PlatformPath {
+    pub start: Vec3,
+    pub end: Vec3,
+    /// Time it takes to get from one end to the other in seconds.
+    pub duration: f32,
+    pub elapsed: f32,
+}
+
+impl Platform {
+    /// Builds the platform from the node's custom properties:
+    /// `offset` (a ping-pong path relative to the start), `duration` and `animation`.
+    pub fn from_name(
+        name: &str,
+        transform: &Transform,
+        extras: &Value,
+    ) -> Option<(Self, Option<PlatformPath>)> {
+        if !has_prefix(name, "platform") {
+            return None;
+        }
+
+        let platform = Platform {
+            animation: extras["animation"].as_str().unwrap_or(name).to_string(),
+        };
+
+        let path = extras_vec3(extras, "offset").map(|offset| PlatformPath {
+            start: transform.translation,
+            end: transform.translation + offset,
+            duration: extras_f32(extras, "duration").unwrap_or(3.).max(0.1),
+            elapsed: 0.,
+        });
+
+        Some((platform, path))
+    }
+}
+
+fn play_platform_animations(
+    mut query: Query<(&Platform, &mut AnimationPlayer), (Added<Platform>, Without<PlatformPath>)>,
+    level_index: Res<LevelIndex>,
+    models: Res<ModelAssets>,
+    gltf_assets: Res<Assets<Gltf>>,
+) {
+    if let Some(gltf) = gltf_assets.get(&models.level_gltfs[level_index.0]) {
+        for (platform, mut player) in &mut query {
+            if let Some(animation) = gltf.named_animations.get(&platform.animation) {
+                player.play(animation.clone_weak()).repeat();
+            } else {
+                warn!("No animation named {} for platform", platform.animation);
+            }
+        }
+    }
+}
+
+fn move_platforms(
+    mut query: Query<(&mut Transform, &mut PlatformPath)>,
+    fixed_time: Res<FixedTime>,
+) {
+    for (mut transform, mut path) in &mut query {
+        path.elapsed += fixed_time.period.as_secs_f32();
+
+        // Ease in and out at both ends so the ball doesn't get flung off
+        let phase = path.elapsed / path.duration * PI;
+        let t = 0.5 - 0.5 * phase.cos();
+
+        transform.translation = path.start.lerp(path.end, t);
+    }
+}
@@ -0,0 +1,155 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{ball::Ball, physics::GameplaySet, util::name_suffix};
+
+/// How far in front of the exit portal the ball appears
+const EXIT_DISTANCE: f32 = 2.;
+const PORTAL_COOLDOWN: f32 = 0.5;
+
+pub struct PortalPlugin;
+
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Portal>()
+            .add_event::<Teleported>()
+            .add_systems(
+                (tick_portal_cooldown, teleport)
+                    .chain()
+                    .in_set(GameplaySet)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+    }
+}
+
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PortalSide {
+    #[default]
+    A,
+    B,
+}
+
+/// Paired by node name, `portal_a_1` with `portal_b_1`. The ball leaves along the exit's forward vector.
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component)]
+pub struct Portal {
+    pub id: String,
+    pub side: PortalSide,
+}
+
+impl Portal {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (side, id) = if let Some(id) = name_suffix(name, "portal_a") {
+            (PortalSide::A, id)
+        } else if let Some(id) = name_suffix(name, "portal_b") {
+            (PortalSide::B, id)
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            id: id.to_string(),
+            side,
+        })
+    }
+}
+
+#[derive(Bundle)]
+pub struct PortalBundle {
+    pub portal: Portal,
+    pub collider: Collider,
+    pub sensor: Sensor,
+}
+
+impl PortalBundle {
+    pub fn new(portal: Portal) -> Self {
+        Self {
+            portal,
+            collider: Collider::cuboid(1., 1., 1.),
+            sensor: Sensor,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Teleported {
+    pub ball: Entity,
+    /// The rotation from the entry portal's frame into the exit portal's frame
+    pub rotation: Quat,
+}
+
+#[derive(Component, Clone, Debug)]
+struct PortalCooldown(Timer);
+
+/// The cooldown keeps the ball from going straight back through the exit.
+fn teleport(
+    mut commands: Commands,
+    mut ball_query: Query<
+        (Entity, &mut Transform, &mut Velocity),
+        (With<Ball>, Without<PortalCooldown>),
+    >,
+    portal_query: Query<(Entity, &Portal, &GlobalTransform)>,
+    rapier_context: Res<RapierContext>,
+    mut teleported: EventWriter<Teleported>,
+) {
+    for (ball, mut transform, mut velocity) in ball_query.iter_mut() {
+        let entry = rapier_context
+            .intersections_with(ball)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(collider1, collider2, _)| {
+                if collider1 == ball {
+                    collider2
+                } else {
+                    collider1
+                }
+            })
+            .find_map(|other| portal_query.get(other).ok());
+
+        let Some((_, entry, entry_transform)) = entry else {
+            continue;
+        };
+
+        let exit = portal_query
+            .iter()
+            .find(|(_, portal, _)| portal.id == entry.id && portal.side != entry.side);
+
+        let Some((_, _, exit_transform)) = exit else {
+            warn!("Portal {} has no partner", entry.id);
+            continue;
+        };
+
+        let (_, entry_rotation, _) = entry_transform.to_scale_rotation_translation();
+        let (_, exit_rotation, exit_translation) = exit_transform.to_scale_rotation_translation();
+
+        // Going into the entry portal's face means coming out of the exit portal's face,
+        // hence the half turn
+        let rotation = exit_rotation * Quat::from_rotation_y(PI) * entry_rotation.inverse();
+
+        transform.translation = exit_translation + exit_transform.forward() * EXIT_DISTANCE;
+        velocity.linvel = rotation * velocity.linvel;
+        velocity.angvel = rotation * velocity.angvel;
+
+        commands
+            .entity(ball)
+            .insert(PortalCooldown(Timer::from_seconds(
+                PORTAL_COOLDOWN,
+                TimerMode::Once,
+            )));
+
+        teleported.send(Teleported { ball, rotation });
+    }
+}
+
+fn tick_portal_cooldown(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PortalCooldown)>,
+    fixed_time: Res<FixedTime>,
+) {
+    for (entity, mut cooldown) in query.iter_mut() {
+        if cooldown.0.tick(fixed_time.period).finished() {
+            commands.entity(entity).remove::<PortalCooldown>();
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::{LevelIndex, LevelMeta},
+    save::{read_save, write_save},
+    stats::LevelStats,
+    GameState,
+};
+
+const PROGRESS_FILE: &str = "progress.ron";
+
+pub struct ProgressPlugin;
+
+impl Plugin for ProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(read_save::<Progress>(PROGRESS_FILE))
+            .add_system(record_progress.in_schedule(OnEnter(GameState::LevelComplete)));
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Progress {
+    pub levels: Vec<LevelRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct LevelRecord {
+    pub completed: bool,
+    pub best_stars: u32,
+    pub stars_total: u32,
+}
+
+impl Progress {
+    pub fn level(&self, index: usize) -> LevelRecord {
+        self.levels.get(index).copied().unwrap_or_default()
+    }
+
+    pub fn total_stars(&self) -> u32 {
+        self.levels.iter().map(|record| record.best_stars).sum()
+    }
+
+    pub fn is_unlocked(&self, meta: &LevelMeta) -> bool {
+        self.total_stars() >= meta.stars_required
+    }
+}
+
+fn record_progress(
+    mut progress: ResMut<Progress>,
+    level_index: Res<LevelIndex>,
+    stats: Res<LevelStats>,
+) {
+    if progress.levels.len() <= level_index.0 {
+        progress
+            .levels
+            .resize(level_index.0 + 1, LevelRecord::default());
+    }
+
+    let record = &mut progress.levels[level_index.0];
+
+    record.completed = true;
+    record.best_stars = record.best_stars.max(stats.stars_collected);
+    record.stars_total = stats.stars_total;
+
+    write_save(PROGRESS_FILE, &*progress);
+}
@@ -0,0 +1,603 @@
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy_rapier3d::prelude::PhysicsSet;
+use bevy_scene_hook::{HookedSceneBundle, SceneHook};
+
+use crate::{
+    ball::{ActiveBall, BallOwner},
+    camera::{CameraControls, CameraMode},
+    level::LevelIndex,
+    loading::{FontAssets, ModelAssets},
+    menu::{button_colors, ButtonColors},
+    util::cleanup,
+    GameState,
+};
+
+const SPEEDS: [f32; 5] = [0.25, 0.5, 1., 2., 4.];
+const NORMAL_SPEED: usize = 2;
+/// How far the arrow keys move through the replay, in seconds.
+const SKIP_TIME: f32 = 2.;
+const FREE_CAMERA_SPEED: f32 = 25.;
+const FREE_CAMERA_SENSITIVITY: f32 = 0.004;
+
+pub struct ReplayPlugin;
+
+/// Only transforms are played back, the physics aren't simulated again.
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunRecording>()
+            .init_resource::<ReplayPlayback>()
+            .add_system(start_recording.in_schedule(OnEnter(GameState::LoadLevel)))
+            .add_system(
+                record_run
+                    .after(PhysicsSet::Writeback)
+                    .run_if(in_state(GameState::Playing))
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(watch_replay_button.in_set(OnUpdate(GameState::LevelComplete)))
+            .add_system(watch_replay_button.in_set(OnUpdate(GameState::Win)))
+            .add_system(spawn_replay.in_schedule(OnEnter(GameState::Replay)))
+            .add_systems(
+                (
+                    replay_keyboard_controls,
+                    replay_buttons,
+                    scrub_timeline,
+                    advance_playback,
+                    apply_replay_frame,
+                    move_free_camera,
+                    update_replay_ui,
+                )
+                    .chain()
+                    .in_set(OnUpdate(GameState::Replay)),
+            )
+            .add_system(button_colors.in_set(OnUpdate(GameState::Replay)))
+            .add_systems(
+                (
+                    cleanup::<ReplayScene>,
+                    cleanup::<ReplayCamera>,
+                    cleanup::<ReplayUi>,
+                )
+                    .in_schedule(OnExit(GameState::Replay)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ReplayFrame {
+    time: f32,
+    ball: Transform,
+    camera: Transform,
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+struct RunRecording {
+    level: usize,
+    frames: Vec<ReplayFrame>,
+}
+
+impl RunRecording {
+    fn duration(&self) -> f32 {
+        self.frames.last().map_or(0., |frame| frame.time)
+    }
+
+    fn sample(&self, time: f32) -> Option<(Transform, Transform)> {
+        let index = self.frames.partition_point(|frame| frame.time < time);
+
+        let (previous, next) = match index {
+            0 => (self.frames.first()?, self.frames.first()?),
+            index if index >= self.frames.len() => (self.frames.last()?, self.frames.last()?),
+            index => (&self.frames[index - 1], &self.frames[index]),
+        };
+
+        let t = if next.time > previous.time {
+            (time - previous.time) / (next.time - previous.time)
+        } else {
+            0.
+        };
+
+        Some((
+            lerp_transform(&previous.ball, &next.ball, t),
+            lerp_transform(&previous.camera, &next.camera, t),
+        ))
+    }
+}
+
+fn lerp_transform(from: &Transform, to: &Transform, t: f32) -> Transform {
+    Transform {
+        translation: from.translation.lerp(to.translation, t),
+        rotation: from.rotation.slerp(to.rotation, t),
+        scale: from.scale.lerp(to.scale, t),
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ReplayCameraMode {
+    #[default]
+    Follow,
+    Free,
+}
+
+#[derive(Resource, Clone, Debug)]
+struct ReplayPlayback {
+    time: f32,
+    speed: usize,
+    paused: bool,
+    camera_mode: ReplayCameraMode,
+    return_state: GameState,
+}
+
+impl Default for ReplayPlayback {
+    fn default() -> Self {
+        Self {
+            time: 0.,
+            speed: NORMAL_SPEED,
+            paused: false,
+            camera_mode: ReplayCameraMode::default(),
+            return_state: GameState::LevelComplete,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WatchReplayButton;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct ReplayScene;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct ReplayBall;
+
+#[derive(Component, Clone, Copy, Debug, Default)]
+struct ReplayCamera {
+    yaw: f32,
+    pitch: f32,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct ReplayUi;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum ReplayControl {
+    PlayPause,
+    Slower,
+    Faster,
+    Camera,
+    Back,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+enum ReplayLabel {
+    PlayPause,
+    Speed,
+    Camera,
+    Time,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct ReplayTimeline;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct ReplayTimelineFill;
+
+fn start_recording(mut recording: ResMut<RunRecording>, level_index: Res<LevelIndex>) {
+    *recording = RunRecording {
+        level: level_index.0,
+        frames: Vec::new(),
+    };
+}
+
+fn record_run(
+    mut recording: ResMut<RunRecording>,
+    ball_query: Query<(&Transform, Option<&Parent>, &BallOwner), With<ActiveBall>>,
+    parent_query: Query<&GlobalTransform>,
+    camera_query: Query<(&Transform, Option<&BallOwner>), With<CameraControls>>,
+    camera_mode: Res<CameraMode>,
+    fixed_time: Res<FixedTime>,
+) {
+    // Nothing happens while the intro plays
+    if matches!(*camera_mode, CameraMode::Intro { .. }) {
+        return;
+    }
+
+    // Racing on split-screen, the replay follows the first player
+    let Some((transform, parent, owner)) = ball_query.iter().min_by_key(|(_, _, owner)| owner.0)
+    else {
+        return;
+    };
+
+    // Global transforms are only updated after the fixed steps, the level itself doesn't move though
+    let ball = parent
+        .and_then(|parent| parent_query.get(parent.get()).ok())
+        .map_or(*transform, |parent| {
+            parent.mul_transform(*transform).compute_transform()
+        });
+
+    let Some((camera, _)) = camera_query
+        .iter()
+        .find(|(_, camera_owner)| owner.matches(*camera_owner))
+    else {
+        return;
+    };
+
+    let time = recording.duration() + fixed_time.period.as_secs_f32();
+
+    recording.frames.push(ReplayFrame {
+        time,
+        ball,
+        camera: *camera,
+    });
+}
+
+fn watch_replay_button(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<WatchReplayButton>)>,
+    current_state: Res<State<GameState>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut playback: ResMut<ReplayPlayback>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            *playback = ReplayPlayback {
+                return_state: current_state.0.clone(),
+                ..default()
+            };
+
+            state.set(GameState::Replay);
+        }
+    }
+}
+
+fn spawn_replay(
+    mut commands: Commands,
+    recording: Res<RunRecording>,
+    models: Res<ModelAssets>,
+    font_assets: Res<FontAssets>,
+    button_colors: Res<ButtonColors>,
+) {
+    // Only the ball is tagged, the rest of the level is just for show
+    commands
+        .spawn(HookedSceneBundle {
+            scene: SceneBundle {
+                scene: models.levels[recording.level].clone(),
+                ..default()
+            },
+            hook: SceneHook::new(|entity, commands| {
+                if entity.get::<Name>().map(|name| name.as_str()) == Some("ball") {
+                    commands.insert(ReplayBall);
+                }
+            }),
+        })
+        .insert(ReplayScene)
+        .insert(Name::new("ReplayScene"));
+
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(ReplayCamera::default());
+
+    let text_style = TextStyle {
+        font: font_assets.poppins.clone(),
+        font_size: 24.,
+        color: Color::WHITE,
+    };
+
+    let button_style = Style {
+        size: Size::new(Val::Px(128.), Val::Px(40.)),
+        margin: UiRect::horizontal(Val::Px(4.)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(0.),
+                    left: Val::Px(0.),
+                    right: Val::Px(0.),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(16.)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.5).into(),
+            ..default()
+        })
+        .insert(ReplayUi)
+        .insert(Name::new("ReplayUi"))
+        .with_children(|parent| {
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.), Val::Px(16.)),
+                        margin: UiRect::bottom(Val::Px(16.)),
+                        ..default()
+                    },
+                    background_color: button_colors.normal.into(),
+                    ..default()
+                })
+                .insert(ReplayTimeline)
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        })
+                        .insert(ReplayTimelineFill);
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let buttons = [
+                        (ReplayControl::Slower, Some("-"), None),
+                        (ReplayControl::PlayPause, None, Some(ReplayLabel::PlayPause)),
+                        (ReplayControl::Faster, Some("+"), None),
+                        (ReplayControl::Camera, None, Some(ReplayLabel::Camera)),
+                        (ReplayControl::Back, Some("Back"), None),
+                    ];
+
+                    for (control, text, label) in buttons {
+                        parent
+                            .spawn(ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: button_colors.normal.into(),
+                                ..default()
+                            })
+                            .insert(control)
+                            .with_children(|parent| {
+                                let mut text_entity = parent.spawn(TextBundle::from_section(
+                                    text.unwrap_or_default(),
+                                    text_style.clone(),
+                                ));
+
+                                if let Some(label) = label {
+                                    text_entity.insert(label);
+                                }
+                            });
+                    }
+
+                    parent
+                        .spawn(TextBundle::from_section("", text_style.clone()))
+                        .insert(ReplayLabel::Speed);
+
+                    parent
+                        .spawn(
+                            TextBundle::from_section("", text_style.clone()).with_style(Style {
+                                margin: UiRect::left(Val::Px(16.)),
+                                ..default()
+                            }),
+                        )
+                        .insert(ReplayLabel::Time);
+                });
+        });
+}
+
+fn replay_keyboard_controls(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut state: ResMut<NextState<GameState>>,
+    recording: Res<RunRecording>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Left) {
+        playback.time = (playback.time - SKIP_TIME).max(0.);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        playback.time = (playback.time + SKIP_TIME).min(recording.duration());
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        playback.speed = playback.speed.saturating_sub(1);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        playback.speed = (playback.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::C) {
+        toggle_camera_mode(&mut playback);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(playback.return_state.clone());
+    }
+}
+
+fn toggle_camera_mode(playback: &mut ReplayPlayback) {
+    playback.camera_mode = match playback.camera_mode {
+        ReplayCameraMode::Follow => ReplayCameraMode::Free,
+        ReplayCameraMode::Free => ReplayCameraMode::Follow,
+    };
+}
+
+fn replay_buttons(
+    interaction_query: Query<(&Interaction, &ReplayControl), Changed<Interaction>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, control) in interaction_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match control {
+            ReplayControl::PlayPause => playback.paused = !playback.paused,
+            ReplayControl::Slower => playback.speed = playback.speed.saturating_sub(1),
+            ReplayControl::Faster => {
+                playback.speed = (playback.speed + 1).min(SPEEDS.len() - 1);
+            }
+            ReplayControl::Camera => toggle_camera_mode(&mut playback),
+            ReplayControl::Back => state.set(playback.return_state.clone()),
+        }
+    }
+}
+
+fn scrub_timeline(
+    timeline_query: Query<(&Interaction, &Node, &GlobalTransform), With<ReplayTimeline>>,
+    windows: Query<&Window>,
+    mut playback: ResMut<ReplayPlayback>,
+    recording: Res<RunRecording>,
+) {
+    let Some(cursor) = windows
+        .iter()
+        .next()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    for (interaction, node, transform) in timeline_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        let left = transform.translation().x - node.size().x / 2.;
+        let progress = ((cursor.x - left) / node.size().x).clamp(0., 1.);
+
+        playback.time = progress * recording.duration();
+    }
+}
+
+fn advance_playback(
+    mut playback: ResMut<ReplayPlayback>,
+    recording: Res<RunRecording>,
+    time: Res<Time>,
+) {
+    if playback.paused {
+        return;
+    }
+
+    playback.time += time.delta_seconds() * SPEEDS[playback.speed];
+
+    if playback.time >= recording.duration() {
+        playback.time = recording.duration();
+        playback.paused = true;
+    }
+}
+
+fn apply_replay_frame(
+    mut ball_query: Query<&mut Transform, With<ReplayBall>>,
+    mut camera_query: Query<(&mut Transform, &mut ReplayCamera), Without<ReplayBall>>,
+    playback: Res<ReplayPlayback>,
+    recording: Res<RunRecording>,
+) {
+    let Some((ball, camera)) = recording.sample(playback.time) else {
+        return;
+    };
+
+    for mut transform in ball_query.iter_mut() {
+        *transform = ball;
+    }
+
+    if playback.camera_mode == ReplayCameraMode::Follow {
+        for (mut transform, mut replay_camera) in camera_query.iter_mut() {
+            *transform = camera;
+
+            // So the free camera starts looking the same way
+            let (yaw, pitch, _) = camera.rotation.to_euler(EulerRot::YXZ);
+            replay_camera.yaw = yaw;
+            replay_camera.pitch = pitch;
+        }
+    }
+}
+
+/// WASD to fly, hold the right mouse button to look around.
+fn move_free_camera(
+    mut camera_query: Query<(&mut Transform, &mut ReplayCamera)>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mouse_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    playback: Res<ReplayPlayback>,
+    time: Res<Time>,
+) {
+    let mouse_delta = mouse_motion.iter().map(|motion| motion.delta).sum::<Vec2>();
+
+    if playback.camera_mode != ReplayCameraMode::Free {
+        return;
+    }
+
+    for (mut transform, mut replay_camera) in camera_query.iter_mut() {
+        if mouse_input.pressed(MouseButton::Right) {
+            replay_camera.yaw -= mouse_delta.x * FREE_CAMERA_SENSITIVITY;
+            replay_camera.pitch =
+                (replay_camera.pitch - mouse_delta.y * FREE_CAMERA_SENSITIVITY).clamp(-1.5, 1.5);
+        }
+
+        transform.rotation =
+            Quat::from_euler(EulerRot::YXZ, replay_camera.yaw, replay_camera.pitch, 0.);
+
+        let mut direction = Vec3::ZERO;
+
+        if keyboard_input.pressed(KeyCode::W) {
+            direction += transform.forward();
+        }
+        if keyboard_input.pressed(KeyCode::S) {
+            direction += transform.back();
+        }
+        if keyboard_input.pressed(KeyCode::A) {
+            direction += transform.left();
+        }
+        if keyboard_input.pressed(KeyCode::D) {
+            direction += transform.right();
+        }
+
+        transform.translation +=
+            direction.normalize_or_zero() * FREE_CAMERA_SPEED * time.delta_seconds();
+    }
+}
+
+fn update_replay_ui(
+    mut label_query: Query<(&mut Text, &ReplayLabel)>,
+    mut fill_query: Query<&mut Style, With<ReplayTimelineFill>>,
+    playback: Res<ReplayPlayback>,
+    recording: Res<RunRecording>,
+) {
+    if !playback.is_changed() {
+        return;
+    }
+
+    for (mut text, label) in label_query.iter_mut() {
+        text.sections[0].value = match label {
+            ReplayLabel::PlayPause => {
+                if playback.paused {
+                    "Play".to_string()
+                } else {
+                    "Pause".to_string()
+                }
+            }
+            ReplayLabel::Speed => format!("{}x", SPEEDS[playback.speed]),
+            ReplayLabel::Camera => match playback.camera_mode {
+                ReplayCameraMode::Follow => "Follow".to_string(),
+                ReplayCameraMode::Free => "Free".to_string(),
+            },
+            ReplayLabel::Time => format!("{:.1} / {:.1}s", playback.time, recording.duration()),
+        };
+    }
+
+    let progress = if recording.duration() > 0. {
+        playback.time / recording.duration()
+    } else {
+        0.
+    };
+
+    for mut style in fill_query.iter_mut() {
+        style.size.width = Val::Percent(progress * 100.);
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::{actions::BurstActions, GameState};
+use crate::{actions::BurstActions, hotseat::Hotseat, GameState};
 
 pub struct ResetPlugin;
 
@@ -10,9 +10,14 @@ impl Plugin for ResetPlugin {
     }
 }
 
-fn reset(mut burst_actions: EventReader<BurstActions>, mut state: ResMut<NextState<GameState>>) {
+fn reset(
+    mut burst_actions: EventReader<BurstActions>,
+    mut state: ResMut<NextState<GameState>>,
+    hotseat: Res<Hotseat>,
+) {
     for action in burst_actions.iter() {
-        if *action == BurstActions::Reset {
+        // Restarting would wipe everyone's strokes, so a player who's behind can't just start over
+        if *action == BurstActions::Reset && !hotseat.is_multiplayer() {
             state.set(GameState::LoadLevel);
         }
     }
@@ -0,0 +1,63 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+/// Registers `T` as an asset that's loaded from RON files with the given extensions.
+pub struct RonAssetPlugin<T> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetPlugin<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Plugin for RonAssetPlugin<T>
+where
+    T: TypeUuid + DeserializeOwned + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.add_asset::<T>().add_asset_loader(RonAssetLoader::<T> {
+            extensions: self.extensions,
+            _marker: PhantomData,
+        });
+    }
+}
+
+struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> AssetLoader for RonAssetLoader<T>
+where
+    T: TypeUuid + DeserializeOwned + Send + Sync + 'static,
+{
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset = ron::de::from_bytes::<T>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
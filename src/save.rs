@@ -0,0 +1,32 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads a RON save file from the working directory, or returns the default if there's none.
+/// Nothing is persisted on the web.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_save<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    std::fs::read_to_string(file_name)
+        .ok()
+        .and_then(|contents| ron::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_save<T: Serialize>(file_name: &str, value: &T) {
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|contents| {
+            std::fs::write(file_name, contents).map_err(|error| error.to_string())
+        });
+
+    if let Err(error) = result {
+        bevy::log::error!("Couldn't write {}: {}", file_name, error);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_save<T: DeserializeOwned + Default>(_file_name: &str) -> T {
+    T::default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_save<T: Serialize>(_file_name: &str, _value: &T) {}
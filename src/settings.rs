@@ -0,0 +1,357 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    loading::FontAssets,
+    menu::{button_colors, ButtonColors},
+    save::{read_save, write_save},
+    util::cleanup,
+    GameState,
+};
+
+const SETTINGS_FILE: &str = "settings.ron";
+/// How much the volume changes per click on - or +.
+const VOLUME_STEP: f32 = 0.1;
+
+pub struct SettingsPlugin;
+
+/// Settings are saved when leaving their screen.
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(read_save::<Settings>(SETTINGS_FILE))
+            .add_system(setup_settings.in_schedule(OnEnter(GameState::Settings)))
+            .add_systems(
+                (
+                    volume_button,
+                    mute_button,
+                    back_button,
+                    update_labels,
+                    button_colors,
+                )
+                    .in_set(OnUpdate(GameState::Settings)),
+            )
+            .add_systems(
+                (
+                    save_settings,
+                    cleanup::<Camera2d>,
+                    cleanup::<SettingsScreen>,
+                )
+                    .in_schedule(OnExit(GameState::Settings)),
+            );
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub music: ChannelSettings,
+    pub sfx: ChannelSettings,
+    pub ui: ChannelSettings,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct ChannelSettings {
+    /// From 0 to 1
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for ChannelSettings {
+    fn default() -> Self {
+        Self {
+            volume: 1.,
+            muted: false,
+        }
+    }
+}
+
+impl ChannelSettings {
+    pub fn volume(&self) -> f64 {
+        if self.muted {
+            0.
+        } else {
+            self.volume as f64
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Channel {
+    Music,
+    Sfx,
+    Ui,
+}
+
+impl Channel {
+    const ALL: [Channel; 3] = [Channel::Music, Channel::Sfx, Channel::Ui];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Channel::Music => "Music",
+            Channel::Sfx => "Effects",
+            Channel::Ui => "Interface",
+        }
+    }
+}
+
+impl Settings {
+    fn channel(&self, channel: Channel) -> &ChannelSettings {
+        match channel {
+            Channel::Music => &self.music,
+            Channel::Sfx => &self.sfx,
+            Channel::Ui => &self.ui,
+        }
+    }
+
+    fn channel_mut(&mut self, channel: Channel) -> &mut ChannelSettings {
+        match channel {
+            Channel::Music => &mut self.music,
+            Channel::Sfx => &mut self.sfx,
+            Channel::Ui => &mut self.ui,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct SettingsScreen;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct VolumeButton {
+    channel: Channel,
+    step: f32,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct MuteButton(Channel);
+
+#[derive(Component, Clone, Copy, Debug)]
+struct BackButton;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct VolumeLabel(Channel);
+
+#[derive(Component, Clone, Copy, Debug)]
+struct MuteLabel(Channel);
+
+fn volume_label(settings: &ChannelSettings) -> String {
+    format!("{:.0}%", settings.volume * 100.)
+}
+
+fn mute_label(settings: &ChannelSettings) -> &'static str {
+    if settings.muted {
+        "Unmute"
+    } else {
+        "Mute"
+    }
+}
+
+fn setup_settings(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    button_colors: Res<ButtonColors>,
+    settings: Res<Settings>,
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font: font_assets.poppins.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    let button = |width: Val| ButtonBundle {
+        style: Style {
+            size: Size::new(width, Val::Px(40.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: button_colors.normal.into(),
+        ..default()
+    };
+
+    commands.spawn(Camera2dBundle::default());
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::all(Val::Percent(100.)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                gap: Size::height(Val::Px(16.)),
+                ..default()
+            },
+            background_color: Color::WHITE.into(),
+            ..default()
+        })
+        .insert(SettingsScreen)
+        .insert(Name::new("SettingsScreen"))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Settings",
+                TextStyle {
+                    color: Color::BLACK,
+                    ..text_style(48.)
+                },
+            ));
+
+            for channel in Channel::ALL {
+                let channel_settings = settings.channel(channel);
+
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(512.), Val::Auto),
+                            align_items: AlignItems::Center,
+                            gap: Size::width(Val::Px(8.)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                channel.name(),
+                                TextStyle {
+                                    color: Color::BLACK,
+                                    ..text_style(28.)
+                                },
+                            )
+                            .with_style(Style {
+                                size: Size::width(Val::Px(160.)),
+                                ..default()
+                            }),
+                        );
+
+                        parent
+                            .spawn(button(Val::Px(40.)))
+                            .insert(VolumeButton {
+                                channel,
+                                step: -VOLUME_STEP,
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section("-", text_style(28.)));
+                            });
+
+                        parent
+                            .spawn(
+                                TextBundle::from_section(
+                                    volume_label(channel_settings),
+                                    TextStyle {
+                                        color: Color::BLACK,
+                                        ..text_style(28.)
+                                    },
+                                )
+                                .with_text_alignment(TextAlignment::Center)
+                                .with_style(Style {
+                                    size: Size::width(Val::Px(80.)),
+                                    justify_content: JustifyContent::Center,
+                                    ..default()
+                                }),
+                            )
+                            .insert(VolumeLabel(channel));
+
+                        parent
+                            .spawn(button(Val::Px(40.)))
+                            .insert(VolumeButton {
+                                channel,
+                                step: VOLUME_STEP,
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section("+", text_style(28.)));
+                            });
+
+                        parent
+                            .spawn(button(Val::Px(128.)))
+                            .insert(MuteButton(channel))
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(TextBundle::from_section(
+                                        mute_label(channel_settings),
+                                        text_style(24.),
+                                    ))
+                                    .insert(MuteLabel(channel));
+                            });
+                    });
+            }
+
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(192.), Val::Px(48.)),
+                        margin: UiRect::top(Val::Px(32.)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: button_colors.normal.into(),
+                    ..default()
+                })
+                .insert(BackButton)
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back", text_style(32.)));
+                });
+        });
+}
+
+fn volume_button(
+    interaction_query: Query<(&Interaction, &VolumeButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            let channel_settings = settings.channel_mut(button.channel);
+
+            // Rounded so repeated steps don't drift away from whole percentages
+            channel_settings.volume = ((channel_settings.volume + button.step) * 10.)
+                .round()
+                .clamp(0., 10.)
+                / 10.;
+            channel_settings.muted = false;
+        }
+    }
+}
+
+fn mute_button(
+    interaction_query: Query<(&Interaction, &MuteButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            let channel_settings = settings.channel_mut(button.0);
+
+            channel_settings.muted = !channel_settings.muted;
+        }
+    }
+}
+
+fn back_button(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            state.set(GameState::Menu);
+        }
+    }
+}
+
+fn update_labels(
+    settings: Res<Settings>,
+    mut volume_labels: Query<(&mut Text, &VolumeLabel), Without<MuteLabel>>,
+    mut mute_labels: Query<(&mut Text, &MuteLabel), Without<VolumeLabel>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (mut text, label) in volume_labels.iter_mut() {
+        text.sections[0].value = volume_label(settings.channel(label.0));
+    }
+
+    for (mut text, label) in mute_labels.iter_mut() {
+        text.sections[0].value = mute_label(settings.channel(label.0)).to_string();
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    write_save(SETTINGS_FILE, &*settings);
+}
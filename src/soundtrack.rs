@@ -1,16 +1,104 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy_kira_audio::prelude::*;
+
+use crate::{
+    audio::{IntensityChannel, MusicChannel, MusicIntensity},
+    ball::{ActiveBall, Ball},
+    level::CurrentLevel,
+    loading::AudioAssets,
+    GameState,
+};
 
-use crate::{loading::AudioAssets, GameState};
+const CROSSFADE: Duration = Duration::from_millis(1500);
+/// Below this fraction of its energy the ball is in trouble, and the music lets the player know.
+const INTENSITY_THRESHOLD: f32 = 0.25;
 
 pub struct SoundtrackPlugin;
 
 impl Plugin for SoundtrackPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(play_soundtrack.in_schedule(OnExit(GameState::Loading)));
+        app.init_resource::<CurrentTrack>()
+            .add_system(play_menu_music.in_schedule(OnEnter(GameState::Menu)))
+            .add_system(play_level_music.in_schedule(OnEnter(GameState::LoadLevel)))
+            .add_system(update_intensity.in_set(OnUpdate(GameState::Playing)))
+            .add_system(calm_music.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+struct CurrentTrack(Option<Handle<AudioSource>>);
+
+/// Returns whether the track changed, it isn't restarted if it's already playing.
+fn crossfade(
+    music: &AudioChannel<MusicChannel>,
+    current_track: &mut CurrentTrack,
+    track: Handle<AudioSource>,
+) -> bool {
+    if current_track.0.as_ref() == Some(&track) {
+        return false;
+    }
+
+    music.stop().fade_out(AudioTween::linear(CROSSFADE));
+    music
+        .play(track.clone())
+        .looped()
+        .fade_in(AudioTween::linear(CROSSFADE));
+
+    current_track.0 = Some(track);
+
+    true
+}
+
+fn play_menu_music(
+    music: Res<AudioChannel<MusicChannel>>,
+    intensity: Res<AudioChannel<IntensityChannel>>,
+    mut current_track: ResMut<CurrentTrack>,
+    audio_assets: Res<AudioAssets>,
+) {
+    if crossfade(&music, &mut current_track, audio_assets.menu_music.clone()) {
+        intensity.stop().fade_out(AudioTween::linear(CROSSFADE));
+    }
+}
+
+fn play_level_music(
+    music: Res<AudioChannel<MusicChannel>>,
+    intensity: Res<AudioChannel<IntensityChannel>>,
+    mut current_track: ResMut<CurrentTrack>,
+    audio_assets: Res<AudioAssets>,
+    asset_server: Res<AssetServer>,
+    current_level: CurrentLevel,
+) {
+    let track = current_level
+        .meta()
+        .and_then(|meta| meta.music.as_ref())
+        .map_or_else(
+            || audio_assets.soundtrack.clone(),
+            |path| asset_server.load(path.as_str()),
+        );
+
+    // The layer is restarted with the track, so the two stay in time
+    if crossfade(&music, &mut current_track, track) {
+        intensity.stop();
+        intensity.play(audio_assets.intensity.clone()).looped();
+    }
+}
+
+fn update_intensity(
+    ball_query: Query<&Ball, With<ActiveBall>>,
+    mut music_intensity: ResMut<MusicIntensity>,
+) {
+    // Racing, it's enough for one of the balls to run low
+    if !ball_query.is_empty() {
+        music_intensity.set_if_neq(MusicIntensity(
+            ball_query
+                .iter()
+                .any(|ball| ball.energy_fraction() < INTENSITY_THRESHOLD),
+        ));
     }
 }
 
-fn play_soundtrack(audio: Res<Audio>, audio_assets: Res<AudioAssets>) {
-    audio.play(audio_assets.soundtrack.clone()).looped();
+fn calm_music(mut music_intensity: ResMut<MusicIntensity>) {
+    music_intensity.set_if_neq(MusicIntensity(false));
 }
@@ -0,0 +1,152 @@
+use bevy::{prelude::*, render::camera::Viewport};
+use bevy_kira_audio::{AudioChannel, AudioControl};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    audio::SfxChannel,
+    ball::{ui::BallUi, ActiveBall, Ball, BallOwner},
+    camera::CameraControls,
+    hotseat::{Finish, Hotseat, PLAYER_COLORS},
+    loading::{AudioAssets, FontAssets},
+    GameState,
+};
+
+/// Below this speed a ball without energy is out of the race.
+const REST_SPEED: f32 = 0.05;
+
+pub struct SplitScreenPlugin;
+
+/// The first player races with the keyboard and mouse, the second with a gamepad.
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_race_banner.in_schedule(OnEnter(GameState::Playing)))
+            .add_systems(
+                (
+                    set_camera_viewports,
+                    retire_exhausted_balls,
+                    update_race_banner,
+                )
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct RaceBanner;
+
+fn set_camera_viewports(
+    mut camera_query: Query<(&mut Camera, &BallOwner), With<CameraControls>>,
+    windows: Query<&Window>,
+    hotseat: Res<Hotseat>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let columns = hotseat.player_count.max(1) as u32;
+    let width = window.resolution.physical_width() / columns;
+    let height = window.resolution.physical_height();
+
+    for (mut camera, owner) in camera_query.iter_mut() {
+        let viewport = Viewport {
+            physical_position: UVec2::new(width * owner.0 as u32, 0),
+            physical_size: UVec2::new(width, height),
+            ..default()
+        };
+
+        // Only touch the camera when something changed, otherwise its projection is rebuilt every frame
+        let unchanged = camera.viewport.as_ref().is_some_and(|current| {
+            current.physical_position == viewport.physical_position
+                && current.physical_size == viewport.physical_size
+        });
+
+        if !unchanged {
+            camera.viewport = Some(viewport);
+        }
+    }
+}
+
+/// If every ball runs out of energy, the round starts over.
+fn retire_exhausted_balls(
+    mut hotseat: ResMut<Hotseat>,
+    ball_query: Query<(&Ball, &BallOwner, &Velocity), With<ActiveBall>>,
+    mut state: ResMut<NextState<GameState>>,
+    audio_assets: Res<AudioAssets>,
+    audio: Res<AudioChannel<SfxChannel>>,
+) {
+    if !hotseat.split_screen || hotseat.anyone_holed() {
+        return;
+    }
+
+    for (ball, owner, velocity) in ball_query.iter() {
+        if hotseat.players[owner.0].finish.is_none()
+            && ball.energy_fraction() <= 0.
+            && velocity.linvel.length() <= REST_SPEED
+        {
+            hotseat.players[owner.0].finish = Some(Finish::OutOfEnergy);
+        }
+    }
+
+    if hotseat.all_finished() {
+        state.set(GameState::LoadLevel);
+
+        audio.play(audio_assets.lose.clone());
+    }
+}
+
+fn spawn_race_banner(mut commands: Commands, font_assets: Res<FontAssets>, hotseat: Res<Hotseat>) {
+    if !hotseat.split_screen {
+        return;
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(48.),
+                    left: Val::Px(0.),
+                    right: Val::Px(0.),
+                    ..default()
+                },
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(BallUi)
+        .insert(Name::new("RaceBanner"))
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font_assets.poppins.clone(),
+                        font_size: 48.,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(RaceBanner);
+        });
+}
+
+fn update_race_banner(hotseat: Res<Hotseat>, mut text_query: Query<&mut Text, With<RaceBanner>>) {
+    if !hotseat.is_changed() {
+        return;
+    }
+
+    let winner = hotseat
+        .players
+        .iter()
+        .position(|player| player.finish == Some(Finish::Holed));
+
+    for mut text in text_query.iter_mut() {
+        match winner {
+            Some(winner) => {
+                text.sections[0].value = format!("Player {} wins the round!", winner + 1);
+                text.sections[0].style.color = PLAYER_COLORS[winner];
+            }
+            None => text.sections[0].value = String::new(),
+        }
+    }
+}
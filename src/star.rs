@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use bevy_kira_audio::{AudioChannel, AudioControl};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    audio::SfxChannel, ball::Ball, loading::AudioAssets, stats::LevelStats, util::has_prefix,
+    GameState,
+};
+
+pub struct StarPlugin;
+
+impl Plugin for StarPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Star>().add_systems(
+            (count_stars, collect_stars, spin_stars).in_set(OnUpdate(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct Star;
+
+#[derive(Bundle)]
+pub struct StarBundle {
+    pub star: Star,
+    pub collider: Collider,
+    pub sensor: Sensor,
+}
+
+impl Default for StarBundle {
+    fn default() -> Self {
+        Self {
+            star: Star,
+            collider: Collider::ball(1.),
+            sensor: Sensor,
+        }
+    }
+}
+
+impl StarBundle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        has_prefix(name, "star").then(Self::default)
+    }
+}
+
+fn count_stars(query: Query<(), Added<Star>>, mut stats: ResMut<LevelStats>) {
+    stats.stars_total += query.iter().count() as u32;
+}
+
+fn collect_stars(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    ball_query: Query<(), With<Ball>>,
+    star_query: Query<(), With<Star>>,
+    mut stats: ResMut<LevelStats>,
+    audio_assets: Res<AudioAssets>,
+    audio: Res<AudioChannel<SfxChannel>>,
+) {
+    for collision in collision_events.iter() {
+        if let CollisionEvent::Started(entity0, entity1, _) = collision {
+            let other = if ball_query.contains(*entity0) {
+                *entity1
+            } else if ball_query.contains(*entity1) {
+                *entity0
+            } else {
+                continue;
+            };
+
+            if star_query.contains(other) {
+                stats.stars_collected += 1;
+
+                audio
+                    .play(audio_assets.pickup.clone())
+                    .with_playback_rate(1.5);
+
+                commands.entity(other).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn spin_stars(mut query: Query<&mut Transform, With<Star>>, time: Res<Time>) {
+    for mut transform in query.iter_mut() {
+        transform.rotate_y(time.delta_seconds() * 2.);
+    }
+}
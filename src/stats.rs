@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::{camera::CameraMode, hole::Won, physics::GameplaySet, GameState};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelStats>()
+            .add_system(reset_stats.in_schedule(OnEnter(GameState::LoadLevel)))
+            .add_system(
+                tick_level_time
+                    .in_set(GameplaySet)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LevelStats {
+    pub time: f32,
+    pub energy_used: f32,
+    pub energy_collected: f32,
+    pub energy_drained: f32,
+    pub orbs_collected: u32,
+    pub stars_collected: u32,
+    pub stars_total: u32,
+}
+
+fn reset_stats(mut stats: ResMut<LevelStats>) {
+    *stats = LevelStats::default();
+}
+
+fn tick_level_time(
+    mut stats: ResMut<LevelStats>,
+    won: Res<Won>,
+    camera_mode: Res<CameraMode>,
+    fixed_time: Res<FixedTime>,
+) {
+    // Counted in physics steps, so a slow frame rate doesn't change the result
+    if !won.0 && !matches!(*camera_mode, CameraMode::Intro { .. } | CameraMode::Photo) {
+        stats.time += fixed_time.period.as_secs_f32();
+    }
+}
@@ -1,56 +1,201 @@
-use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_kira_audio::AudioChannel;
 use bevy_rapier3d::prelude::{RapierContext, Velocity};
+use serde_json::Value;
 
-use crate::{ball::Ball, loading::AudioAssets, GameState};
+use crate::{
+    audio::{SoundEmitter, SpatialChannel},
+    ball::Ball,
+    loading::AudioAssets,
+    physics::GameplaySet,
+    util::{extras_f32, has_prefix},
+    GameState,
+};
+
+/// A ball moving away from a trampoline faster than this doesn't bounce when it touches it.
+const LEAVING_SPEED: f32 = 0.5;
 
 pub struct TrampolinePlugin;
 
 impl Plugin for TrampolinePlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Trampoline>()
-            .add_system(jump.in_set(OnUpdate(GameState::Playing)));
+            .add_event::<TrampolineBounced>()
+            .add_system(
+                jump.in_set(GameplaySet)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(play_bounce_sounds.in_set(OnUpdate(GameState::Playing)));
     }
 }
 
 #[derive(Component, Reflect, Clone, Copy, Debug)]
 #[reflect(Component)]
 pub struct Trampoline {
-    pub force: f32,
+    /// How much of the speed the ball lands with is kept for the bounce
+    pub restitution: f32,
+    /// The ball leaves at least this fast, even if it barely touched the trampoline
+    pub min_launch_speed: f32,
+    pub enabled: bool,
 }
 
 impl Default for Trampoline {
     fn default() -> Self {
-        Self { force: 25. }
+        Self {
+            restitution: 0.9,
+            min_launch_speed: 25.,
+            enabled: true,
+        }
+    }
+}
+
+impl Trampoline {
+    pub fn from_name(name: &str, extras: &Value) -> Option<Self> {
+        if !has_prefix(name, "trampoline") {
+            return None;
+        }
+
+        let default = Self::default();
+
+        Some(Self {
+            restitution: extras_f32(extras, "restitution").unwrap_or(default.restitution),
+            min_launch_speed: extras_f32(extras, "min_launch_speed")
+                .unwrap_or(default.min_launch_speed),
+            enabled: extras["enabled"].as_bool().unwrap_or(true),
+        })
+    }
+
+    /// Only the part of `velocity` going into the trampoline along `up` is changed.
+    pub fn bounce(&self, velocity: Vec3, up: Vec3) -> Vec3 {
+        let normal_speed = velocity.dot(up);
+        let launch_speed = (-normal_speed * self.restitution).max(self.min_launch_speed);
+
+        velocity + up * (launch_speed - normal_speed)
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+struct TrampolineBounced {
+    trampoline: Entity,
+}
+
+/// The solver has already handled the landing by now,
+/// so the bounce uses the velocity from before the step where it landed.
 fn jump(
     mut ball_query: Query<(Entity, &mut Velocity), With<Ball>>,
-    booster_query: Query<(&Trampoline, &Transform)>,
-    booster_mesh_query: Query<&Parent, With<Handle<Mesh>>>,
+    trampoline_query: Query<(&Trampoline, &GlobalTransform)>,
+    trampoline_mesh_query: Query<&Parent, With<Handle<Mesh>>>,
     rapier_context: Res<RapierContext>,
-    audio: Res<Audio>,
-    audio_assets: Res<AudioAssets>,
+    mut previous_velocities: Local<HashMap<Entity, Vec3>>,
+    // Pairs of ball and trampoline that were already touching, so resting on one doesn't count as landing
+    mut touching: Local<HashSet<(Entity, Entity)>>,
+    mut trampoline_bounced: EventWriter<TrampolineBounced>,
 ) {
-    if let Ok((ball, mut ball_velocity)) = ball_query.get_single_mut() {
-        for contact_pair in rapier_context.contacts_with(ball) {
-            let other = if contact_pair.collider1() == ball {
-                contact_pair.collider2()
-            } else {
-                contact_pair.collider1()
-            };
+    let mut touching_now = HashSet::default();
 
-            if let Ok(parent) = booster_mesh_query.get(other) {
-                if let Ok((trampoline, transform)) = booster_query.get(parent.get()) {
-                    let direction = transform.up();
-                    let boost_vector = direction * trampoline.force;
+    for (ball, mut ball_velocity) in ball_query.iter_mut() {
+        let trampolines = rapier_context
+            .contacts_with(ball)
+            .filter(|contact_pair| contact_pair.has_any_active_contacts())
+            .filter_map(|contact_pair| {
+                let other = if contact_pair.collider1() == ball {
+                    contact_pair.collider2()
+                } else {
+                    contact_pair.collider1()
+                };
 
-                    ball_velocity.linvel += boost_vector;
+                trampoline_mesh_query
+                    .get(other)
+                    .ok()
+                    .map(|parent| parent.get())
+            })
+            .collect::<Vec<_>>();
 
-                    audio.play(audio_assets.boing.clone());
-                }
+        for trampoline_entity in trampolines {
+            if !touching_now.insert((ball, trampoline_entity))
+                || touching.contains(&(ball, trampoline_entity))
+            {
+                continue;
             }
+
+            let Ok((trampoline, transform)) = trampoline_query.get(trampoline_entity) else {
+                continue;
+            };
+
+            let up = transform.up();
+            let previous_velocity = previous_velocities.get(&ball).copied().unwrap_or_default();
+
+            // Already on its way up, like after bouncing off another part of the same trampoline
+            if !trampoline.enabled || previous_velocity.dot(up) > LEAVING_SPEED {
+                continue;
+            }
+
+            ball_velocity.linvel = trampoline.bounce(previous_velocity, up);
+
+            trampoline_bounced.send(TrampolineBounced {
+                trampoline: trampoline_entity,
+            });
+        }
+    }
+
+    *touching = touching_now;
+
+    previous_velocities.clear();
+
+    for (ball, ball_velocity) in ball_query.iter() {
+        previous_velocities.insert(ball, ball_velocity.linvel);
+    }
+}
+
+fn play_bounce_sounds(
+    mut trampoline_bounced: EventReader<TrampolineBounced>,
+    mut emitter_query: Query<&mut SoundEmitter>,
+    spatial: Res<AudioChannel<SpatialChannel>>,
+    audio_assets: Res<AudioAssets>,
+) {
+    for bounce in trampoline_bounced.iter() {
+        if let Ok(mut emitter) = emitter_query.get_mut(bounce.trampoline) {
+            emitter.play(&spatial, audio_assets.boing.clone());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounce_keeps_restitution_of_a_fast_landing() {
+        let trampoline = Trampoline {
+            restitution: 0.5,
+            min_launch_speed: 10.,
+            enabled: true,
+        };
+
+        let velocity = trampoline.bounce(Vec3::new(3., -40., 0.), Vec3::Y);
+
+        assert_eq!(velocity, Vec3::new(3., 20., 0.));
+    }
+
+    #[test]
+    fn bounce_launches_a_slow_landing_at_min_speed() {
+        let trampoline = Trampoline::default();
+
+        let velocity = trampoline.bounce(Vec3::new(0., -1., 2.), Vec3::Y);
+
+        assert_eq!(velocity, Vec3::new(0., trampoline.min_launch_speed, 2.));
+    }
+
+    #[test]
+    fn bounce_follows_a_tilted_trampoline() {
+        let trampoline = Trampoline::default();
+        let up = Vec3::new(1., 1., 0.).normalize();
+
+        let velocity = trampoline.bounce(Vec3::ZERO, up);
+
+        assert!(velocity.abs_diff_eq(up * trampoline.min_launch_speed, 1e-5));
+    }
+}
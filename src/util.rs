@@ -1,7 +1,56 @@
-use bevy::prelude::*;
+use bevy::{gltf::GltfExtras, prelude::*};
+use serde_json::Value;
 
 pub fn cleanup<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
+
+/// Whether a node name is `prefix` on its own or followed by an id, so `speed.001` and `water_2`
+/// match `speed` and `water` but `camera` doesn't match `cam`.
+pub fn has_prefix(name: &str, prefix: &str) -> bool {
+    name.strip_prefix(prefix).is_some_and(|rest| {
+        rest.is_empty() || rest.starts_with(|c: char| c.is_ascii_digit() || c == '_' || c == '.')
+    })
+}
+
+/// Returns the id that follows a node name's prefix, so `door_3.001` gives `3` for `door`.
+/// The `.001` suffix is what blender adds to duplicated objects.
+pub fn name_suffix<'a>(name: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = name.strip_prefix(prefix)?.strip_prefix('_')?;
+    let id = rest.split('.').next().unwrap_or(rest);
+
+    if id.is_empty() {
+        None
+    } else {
+        Some(id)
+    }
+}
+
+/// Parses the custom properties exported with a glTF node.
+/// Returns `Value::Null` if the node has none or they aren't valid JSON.
+pub fn parse_extras(extras: Option<&GltfExtras>) -> Value {
+    extras
+        .and_then(|extras| serde_json::from_str(&extras.value).ok())
+        .unwrap_or_default()
+}
+
+pub fn extras_f32(extras: &Value, key: &str) -> Option<f32> {
+    extras[key].as_f64().map(|value| value as f32)
+}
+
+/// Reads a `[x, y, z]` array in glTF (Y-up) coordinates.
+pub fn extras_vec3(extras: &Value, key: &str) -> Option<Vec3> {
+    let array = extras[key].as_array()?;
+
+    if array.len() != 3 {
+        return None;
+    }
+
+    Some(Vec3::new(
+        array[0].as_f64()? as f32,
+        array[1].as_f64()? as f32,
+        array[2].as_f64()? as f32,
+    ))
+}
@@ -1,8 +1,10 @@
 use bevy::prelude::*;
 
 use crate::{
+    hotseat::{Hotseat, Scorecard},
     loading::{FontAssets, TextureAssets},
     menu::{ButtonColors, button_colors},
+    replay::WatchReplayButton,
     GameState, util::cleanup,
 };
 
@@ -29,7 +31,32 @@ fn spawn_win_screen(
     font_assets: Res<FontAssets>,
     texture_assets: Res<TextureAssets>,
     button_colors: Res<ButtonColors>,
+    hotseat: Res<Hotseat>,
+    scorecard: Res<Scorecard>,
 ) {
+    let (headline, score_lines) = if hotseat.is_multiplayer() {
+        let headline = match scorecard.winner(&hotseat) {
+            Some(winner) if hotseat.split_screen => format!(
+                "Player {} wins the race with {} rounds!",
+                winner + 1,
+                scorecard.rounds_won(winner)
+            ),
+            Some(winner) => format!(
+                "Player {} wins with {} strokes!",
+                winner + 1,
+                scorecard.total(winner)
+            ),
+            None => "It's a tie!".to_string(),
+        };
+
+        (headline, scorecard.lines(&hotseat))
+    } else {
+        (
+            "Congratulations! You completed the game.".to_string(),
+            Vec::new(),
+        )
+    };
+
     commands.spawn(Camera2dBundle::default());
     commands
         .spawn(NodeBundle {
@@ -67,7 +94,7 @@ fn spawn_win_screen(
 
                     parent.spawn(
                         TextBundle::from_section(
-                            "Congratulations! You completed the game.",
+                            headline,
                             TextStyle {
                                 font: font_assets.poppins.clone(),
                                 font_size: 32.,
@@ -75,11 +102,38 @@ fn spawn_win_screen(
                             },
                         )
                         .with_style(Style {
-                            margin: UiRect::bottom(Val::Px(64.)),
+                            margin: UiRect::bottom(Val::Px(if score_lines.is_empty() {
+                                64.
+                            } else {
+                                16.
+                            })),
                             ..default()
                         }),
                     );
 
+                    for (index, line) in score_lines.iter().enumerate() {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                line,
+                                TextStyle {
+                                    font: font_assets.poppins.clone(),
+                                    font_size: 24.,
+                                    color: Color::BLACK,
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::bottom(Val::Px(
+                                    if index + 1 == score_lines.len() {
+                                        64.
+                                    } else {
+                                        0.
+                                    },
+                                )),
+                                ..default()
+                            }),
+                        );
+                    }
+
                     parent
                         .spawn(ButtonBundle {
                             style: Style {
@@ -102,6 +156,30 @@ fn spawn_win_screen(
                                 },
                             ));
                         });
+
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(48.), Val::Px(48.)),
+                                margin: UiRect::top(Val::Px(16.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        })
+                        .insert(WatchReplayButton)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Watch replay",
+                                TextStyle {
+                                    font: font_assets.poppins.clone(),
+                                    font_size: 32.,
+                                    color: Color::WHITE,
+                                },
+                            ));
+                        });
                 });
         });
 }
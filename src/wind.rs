@@ -0,0 +1,213 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use serde_json::Value;
+
+use crate::{
+    ball::Ball,
+    physics::GameplaySet,
+    util::{cleanup, extras_f32, has_prefix},
+    GameState,
+};
+
+const STREAKS_PER_ZONE: usize = 24;
+
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<WindZone>()
+            .init_resource::<WindTime>()
+            .add_system(reset_wind_time.in_schedule(OnEnter(GameState::LoadLevel)))
+            .add_system(
+                blow.in_set(GameplaySet)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_systems(
+                (spawn_wind_streaks, move_wind_streaks).in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(cleanup::<WindStreak>.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+/// The collider is a unit cube, so the zone's size comes from the node's scale.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct WindZone {
+    pub force: f32,
+    /// How much the force varies over time, as a fraction of `force`.
+    pub gust: f32,
+    pub gust_frequency: f32,
+}
+
+impl Default for WindZone {
+    fn default() -> Self {
+        Self {
+            force: 20.,
+            gust: 0.,
+            gust_frequency: 0.5,
+        }
+    }
+}
+
+impl WindZone {
+    pub fn from_name(name: &str, extras: &Value) -> Option<Self> {
+        if !has_prefix(name, "wind") {
+            return None;
+        }
+
+        let default = Self::default();
+
+        Some(Self {
+            force: extras_f32(extras, "force").unwrap_or(default.force),
+            gust: extras_f32(extras, "gust").unwrap_or(default.gust),
+            gust_frequency: extras_f32(extras, "gust_frequency").unwrap_or(default.gust_frequency),
+        })
+    }
+
+    pub fn strength(&self, elapsed_seconds: f32) -> f32 {
+        let phase = elapsed_seconds * self.gust_frequency * TAU;
+        // Two out of phase waves so the gusts don't feel too regular
+        let wave = 0.7 * phase.sin() + 0.3 * (phase * 2.3).sin();
+
+        self.force * (1. + self.gust * wave).max(0.)
+    }
+}
+
+#[derive(Bundle)]
+pub struct WindZoneBundle {
+    pub wind_zone: WindZone,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub visibility: Visibility,
+}
+
+impl WindZoneBundle {
+    pub fn new(wind_zone: WindZone) -> Self {
+        Self {
+            wind_zone,
+            collider: Collider::cuboid(1., 1., 1.),
+            sensor: Sensor,
+            visibility: Visibility::Hidden,
+        }
+    }
+}
+
+/// How long the level has been simulated, counted in physics steps so gusts hit the same at any frame rate.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+struct WindTime(f32);
+
+#[derive(Component, Clone, Copy, Debug)]
+struct WindStreak {
+    zone: Entity,
+    /// Position inside the zone's unit cube
+    local_position: Vec3,
+}
+
+fn reset_wind_time(mut wind_time: ResMut<WindTime>) {
+    wind_time.0 = 0.;
+}
+
+fn blow(
+    mut ball_query: Query<(Entity, &mut ExternalForce), With<Ball>>,
+    wind_query: Query<(&WindZone, &Transform)>,
+    rapier_context: Res<RapierContext>,
+    mut wind_time: ResMut<WindTime>,
+    fixed_time: Res<FixedTime>,
+) {
+    wind_time.0 += fixed_time.period.as_secs_f32();
+
+    for (ball, mut external_force) in ball_query.iter_mut() {
+        let mut force = Vec3::ZERO;
+
+        for (collider1, collider2, intersecting) in rapier_context.intersections_with(ball) {
+            if !intersecting {
+                continue;
+            }
+
+            let other = if collider1 == ball {
+                collider2
+            } else {
+                collider1
+            };
+
+            if let Ok((wind_zone, transform)) = wind_query.get(other) {
+                force += transform.forward() * wind_zone.strength(wind_time.0);
+            }
+        }
+
+        if external_force.force != force {
+            external_force.force = force;
+        }
+    }
+}
+
+fn spawn_wind_streaks(
+    mut commands: Commands,
+    wind_query: Query<Entity, Added<WindZone>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if wind_query.is_empty() {
+        return;
+    }
+
+    let mesh = meshes.add(shape::Box::new(0.04, 0.04, 0.8).into());
+    let material = materials.add(StandardMaterial {
+        base_color: Color::rgba(1., 1., 1., 0.35),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    let mut rng = rand::thread_rng();
+
+    for zone in wind_query.iter() {
+        for _ in 0..STREAKS_PER_ZONE {
+            commands
+                .spawn(PbrBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    ..default()
+                })
+                .insert(WindStreak {
+                    zone,
+                    local_position: Vec3::new(
+                        rng.gen_range(-1.0..1.0),
+                        rng.gen_range(-1.0..1.0),
+                        rng.gen_range(-1.0..1.0),
+                    ),
+                })
+                .insert(Name::new("WindStreak"));
+        }
+    }
+}
+
+fn move_wind_streaks(
+    mut streak_query: Query<(&mut Transform, &mut WindStreak)>,
+    wind_query: Query<(&WindZone, &GlobalTransform)>,
+    wind_time: Res<WindTime>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (mut transform, mut streak) in streak_query.iter_mut() {
+        if let Ok((wind_zone, zone_transform)) = wind_query.get(streak.zone) {
+            let (scale, rotation, _) = zone_transform.to_scale_rotation_translation();
+
+            let speed = wind_zone.strength(wind_time.0) * 0.5;
+            streak.local_position.z -= speed * time.delta_seconds() / scale.z.max(0.01);
+
+            // Wrap around to the back of the zone
+            if streak.local_position.z < -1. {
+                streak.local_position =
+                    Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 1.);
+            }
+
+            transform.translation = zone_transform.transform_point(streak.local_position);
+            transform.rotation = rotation;
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+use serde_json::Value;
+
+use crate::{
+    ball::Ball,
+    booster::Booster,
+    trampoline::Trampoline,
+    util::{has_prefix, name_suffix},
+    GameState,
+};
+
+pub struct WiringPlugin;
+
+/// Seconds a switch ignores the ball after being pressed.
+const SWITCH_COOLDOWN: f32 = 1.;
+
+/// Switches toggle every object with the same link id, from the node name (`switch_3` and `door_3`)
+/// or a `link` custom property.
+impl Plugin for WiringPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Switch>()
+            .register_type::<Door>()
+            .register_type::<LinkTarget>()
+            .init_resource::<LinkGraph>()
+            .add_event::<SwitchToggled>()
+            .add_system(clear_link_graph.in_schedule(OnEnter(GameState::LoadLevel)))
+            .add_systems(
+                (
+                    build_link_graph,
+                    detach_switch_colliders,
+                    tick_switch_cooldown,
+                    press_switches,
+                    toggle_targets,
+                    update_doors,
+                )
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component)]
+pub struct Switch {
+    pub link: String,
+    pub on: bool,
+}
+
+/// A door disappears along with its collider while it's open.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct Door {
+    pub open: bool,
+}
+
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component)]
+pub struct LinkTarget {
+    pub link: String,
+}
+
+impl Switch {
+    pub fn from_name(name: &str, extras: &Value) -> Option<Self> {
+        let link = link_id(name, "switch", extras)?;
+
+        Some(Self { link, on: false })
+    }
+}
+
+impl Door {
+    pub fn from_name(name: &str, extras: &Value) -> Option<(Self, LinkTarget)> {
+        let link = link_id(name, "door", extras)?;
+
+        Some((
+            Self {
+                open: extras["open"].as_bool().unwrap_or(false),
+            },
+            LinkTarget { link },
+        ))
+    }
+}
+
+impl LinkTarget {
+    pub fn from_extras(extras: &Value) -> Option<Self> {
+        extras["link"].as_str().map(|link| Self {
+            link: link.to_string(),
+        })
+    }
+}
+
+fn link_id(name: &str, prefix: &str, extras: &Value) -> Option<String> {
+    if !has_prefix(name, prefix) {
+        return None;
+    }
+
+    extras["link"]
+        .as_str()
+        .or_else(|| name_suffix(name, prefix))
+        .map(|link| link.to_string())
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct LinkGraph(HashMap<String, Vec<Entity>>);
+
+#[derive(Clone, Debug)]
+pub struct SwitchToggled {
+    pub link: String,
+}
+
+fn clear_link_graph(mut link_graph: ResMut<LinkGraph>) {
+    link_graph.0.clear();
+}
+
+fn build_link_graph(
+    query: Query<(Entity, &LinkTarget), Added<LinkTarget>>,
+    mut link_graph: ResMut<LinkGraph>,
+) {
+    for (entity, target) in query.iter() {
+        link_graph
+            .0
+            .entry(target.link.clone())
+            .or_default()
+            .push(entity);
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+struct SwitchCooldown(Timer);
+
+/// Moves a switch's collider off its mesh onto an entity of its own, so squashing the mesh
+/// when the switch is pressed doesn't pull the collider out from under the ball.
+fn detach_switch_colliders(
+    mut commands: Commands,
+    collider_query: Query<
+        (Entity, &Parent, &Collider, &Transform),
+        (Added<Collider>, With<Handle<Mesh>>),
+    >,
+    switch_query: Query<(), With<Switch>>,
+) {
+    for (entity, parent, collider, transform) in collider_query.iter() {
+        if !switch_query.contains(parent.get()) {
+            continue;
+        }
+
+        commands.entity(entity).remove::<Collider>();
+
+        let switch_collider = commands
+            .spawn(TransformBundle::from_transform(*transform))
+            .insert(collider.clone())
+            .insert(Friction::new(1.))
+            .insert(Name::new("SwitchCollider"))
+            .id();
+
+        commands.entity(parent.get()).add_child(switch_collider);
+    }
+}
+
+fn tick_switch_cooldown(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut SwitchCooldown)>,
+    time: Res<Time>,
+) {
+    for (entity, mut cooldown) in query.iter_mut() {
+        if cooldown.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<SwitchCooldown>();
+        }
+    }
+}
+
+fn press_switches(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    ball_query: Query<(), With<Ball>>,
+    mut switch_query: Query<(Entity, &mut Switch, &Children), Without<SwitchCooldown>>,
+    switch_collider_query: Query<&Parent, With<Collider>>,
+    mut mesh_query: Query<&mut Transform, With<Handle<Mesh>>>,
+    mut switch_toggled: EventWriter<SwitchToggled>,
+) {
+    // The cooldown is only added at the end of the frame, this catches a second hit before that
+    let mut pressed = Vec::new();
+
+    for collision in collision_events.iter() {
+        if let CollisionEvent::Started(entity0, entity1, _) = collision {
+            let other = if ball_query.contains(*entity0) {
+                *entity1
+            } else if ball_query.contains(*entity1) {
+                *entity0
+            } else {
+                continue;
+            };
+
+            if let Ok(parent) = switch_collider_query.get(other) {
+                if let Ok((entity, mut switch, children)) = switch_query.get_mut(parent.get()) {
+                    if pressed.contains(&entity) {
+                        continue;
+                    }
+
+                    pressed.push(entity);
+                    switch.on = !switch.on;
+
+                    // Squash the button so the player can see it's been pressed
+                    for child in children.iter() {
+                        if let Ok(mut transform) = mesh_query.get_mut(*child) {
+                            transform.scale.y = if switch.on { 0.5 } else { 1. };
+                        }
+                    }
+
+                    commands
+                        .entity(entity)
+                        .insert(SwitchCooldown(Timer::from_seconds(
+                            SWITCH_COOLDOWN,
+                            TimerMode::Once,
+                        )));
+
+                    switch_toggled.send(SwitchToggled {
+                        link: switch.link.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn toggle_targets(
+    mut switch_toggled: EventReader<SwitchToggled>,
+    link_graph: Res<LinkGraph>,
+    mut door_query: Query<&mut Door>,
+    mut booster_query: Query<&mut Booster>,
+    mut trampoline_query: Query<&mut Trampoline>,
+) {
+    for event in switch_toggled.iter() {
+        let Some(targets) = link_graph.0.get(&event.link) else {
+            warn!("Switch {} isn't wired to anything", event.link);
+            continue;
+        };
+
+        for target in targets {
+            if let Ok(mut door) = door_query.get_mut(*target) {
+                door.open = !door.open;
+            }
+
+            if let Ok(mut booster) = booster_query.get_mut(*target) {
+                booster.enabled = !booster.enabled;
+            }
+
+            if let Ok(mut trampoline) = trampoline_query.get_mut(*target) {
+                trampoline.enabled = !trampoline.enabled;
+            }
+        }
+    }
+}
+
+fn update_doors(
+    mut commands: Commands,
+    mut door_query: Query<(&Door, &mut Visibility, &Children), Changed<Door>>,
+    collider_query: Query<(), With<Collider>>,
+    new_collider_query: Query<(Entity, &Parent), Added<Collider>>,
+    all_doors_query: Query<&Door>,
+) {
+    // Colliders are generated a few frames after the level spawns
+    for (entity, parent) in new_collider_query.iter() {
+        if let Ok(door) = all_doors_query.get(parent.get()) {
+            if door.open {
+                commands.entity(entity).insert(ColliderDisabled);
+            }
+        }
+    }
+
+    for (door, mut visibility, children) in door_query.iter_mut() {
+        *visibility = if door.open {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+
+        for child in children.iter() {
+            if collider_query.contains(*child) {
+                if door.open {
+                    commands.entity(*child).insert(ColliderDisabled);
+                } else {
+                    commands.entity(*child).remove::<ColliderDisabled>();
+                }
+            }
+        }
+    }
+}